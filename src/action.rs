@@ -1,12 +1,324 @@
-use pueue_lib::State;
+use std::time::Duration;
+
+use crossterm::event::KeyEvent;
+use pueue_lib::{Task, message::AddRequest};
 use serde::{Deserialize, Serialize};
 
+use crate::{app::Mode, client::TaskLog, state_diff::StateDiff, widgets::task_table::SortKey};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
    Tick,
-   Render,
    Resize(u16, u16),
    Quit,
    Error(String),
-   UpdateStatus(State),
+   Info(String),
+   /// The changes of the state of the daemon since the last update.
+   StateChanged(Box<StateDiff>),
+   /// The daemon can't be reached, the next attempt to reconnect is in `retry_in`.
+   Disconnected {
+      error: String,
+      retry_in: Duration,
+   },
+   /// The daemon can be reached again after being disconnected.
+   Connected,
+   /// The protocol version of the daemon, whenever it changes.
+   DaemonVersion {
+      version: String,
+      compatible: bool,
+   },
+   SwitchMode(Mode),
+   /// Show the key bindings of the current mode.
+   OpenHelp,
+   CloseHelp,
+   /// Show the history of the info and error messages.
+   OpenMessages,
+   CloseMessages,
+   ClearMessages,
+   /// Open the `:` command line.
+   OpenCommandLine,
+   /// Open the command line with a list of all commands.
+   OpenPalette,
+   SubmitCommand,
+   /// Complete the word before the cursor of the command line.
+   CompleteCommand,
+   NextCompletion,
+   PrevCompletion,
+   /// The count and keys typed so far of an unfinished key sequence.
+   PendingKeys {
+      count: Option<usize>,
+      keys: Vec<KeyEvent>,
+   },
+   NextGroup,
+   PrevGroup,
+   SelectNext,
+   SelectPrev,
+   SelectFirst,
+   SelectLast,
+   /// Show the details of the selected task.
+   OpenSelection,
+   ClearSearch,
+   ApplyFilter,
+   CancelFilter,
+   OpenDetail(usize),
+   UpdateLog(Box<TaskLog>),
+   AppendLog(usize, String),
+   ToggleDetailView,
+   ScrollUp,
+   ScrollDown,
+   ScrollPageUp,
+   ScrollPageDown,
+   ScrollTop,
+   ScrollBottom,
+   /// Mark or unmark the selected task and move to the next one.
+   ToggleMark,
+   /// Start or end marking a range of tasks.
+   ToggleVisualMark,
+   /// Mark all visible tasks, or unmark them if they are all marked.
+   ToggleMarkAll,
+   ClearMarks,
+   /// Apply an action to the marked tasks, or to the selected task if none are marked.
+   ApplyToSelection(TaskAction),
+   ApplyToTasks(TaskAction, Vec<Task>),
+   /// Apply an action to the tasks with these ids, in any group.
+   ApplyToTaskIds(TaskAction, Vec<usize>),
+   /// Filter the tasks with a filter expression, or show all tasks if it is empty.
+   SetFilter(String),
+   SortTasks(Vec<SortKey>),
+   /// Sort by the next column of the table.
+   CycleSort,
+   /// Reverse the order of the column the tasks are sorted by.
+   ReverseSort,
+   AddGroup {
+      name: String,
+      parallel: Option<usize>,
+   },
+   RemoveGroup(String),
+   SetParallel {
+      group: String,
+      parallel: usize,
+   },
+   /// Open the add-task form for the current group with the marked tasks as dependencies.
+   NewTask,
+   OpenAddTask {
+      group: Option<String>,
+      dependencies: Vec<usize>,
+   },
+   SubmitAddTask,
+   /// Move the focus to the next field of a form.
+   NextField,
+   PrevField,
+   AddTask(Box<AddRequest>),
+   /// The daemon didn't add the task, with the reason.
+   AddTaskFailed(String),
+   /// Edit the selected task if it is stashed or queued.
+   EditSelection,
+   EditTask(usize),
+   /// Select the task once it is shown in the table.
+   SelectTask(usize),
+   /// Remove the finished tasks of the selected group.
+   Clean,
+   /// Kill and remove all tasks of the selected group.
+   Reset,
+   /// Shut down the daemon.
+   Shutdown,
+   CleanGroup(Option<String>),
+   ResetGroup(Option<String>),
+   ShutdownDaemon,
+   /// Ask the user before running an action.
+   RequestConfirmation(Box<Confirmation>),
+   ConfirmAccept,
+   ConfirmReject,
+   /// Move the focus between the answers of the confirmation dialog.
+   ConfirmToggle,
+   /// Answer the confirmation dialog with the focused answer.
+   ConfirmSubmit,
+}
+
+impl Action {
+   pub fn category(&self) -> Category {
+      match self {
+         Action::SelectNext
+         | Action::SelectPrev
+         | Action::SelectFirst
+         | Action::SelectLast
+         | Action::OpenSelection
+         | Action::NextGroup
+         | Action::PrevGroup
+         | Action::ScrollUp
+         | Action::ScrollDown
+         | Action::ScrollPageUp
+         | Action::ScrollPageDown
+         | Action::ScrollTop
+         | Action::ScrollBottom
+         | Action::ToggleDetailView
+         | Action::NextField
+         | Action::PrevField => Category::Navigation,
+         Action::SwitchMode(Mode::Search | Mode::Filter)
+         | Action::SetFilter(_)
+         | Action::SortTasks(_)
+         | Action::CycleSort
+         | Action::ReverseSort
+         | Action::ClearSearch
+         | Action::ApplyFilter
+         | Action::CancelFilter => Category::Search,
+         Action::ToggleMark
+         | Action::ToggleVisualMark
+         | Action::ToggleMarkAll
+         | Action::ClearMarks => Category::Marks,
+         Action::ApplyToSelection(_)
+         | Action::NewTask
+         | Action::SubmitAddTask
+         | Action::EditSelection => Category::Tasks,
+         Action::Clean
+         | Action::Reset
+         | Action::Shutdown
+         | Action::AddGroup { .. }
+         | Action::RemoveGroup(_)
+         | Action::SetParallel { .. } => Category::Daemon,
+         _ => Category::General,
+      }
+   }
+
+   /// A short description of the action for the help and the key hints.
+   pub fn description(&self) -> String {
+      match self {
+         Action::Quit => "Quit".to_string(),
+         Action::SwitchMode(Mode::Home) => "Back".to_string(),
+         Action::SwitchMode(Mode::Search) => "Search".to_string(),
+         Action::SwitchMode(Mode::Filter) => "Filter".to_string(),
+         Action::OpenHelp => "Help".to_string(),
+         Action::CloseHelp => "Close help".to_string(),
+         Action::OpenMessages => "Messages".to_string(),
+         Action::CloseMessages => "Close messages".to_string(),
+         Action::ClearMessages => "Clear messages".to_string(),
+         Action::OpenCommandLine => "Command line".to_string(),
+         Action::OpenPalette => "Command palette".to_string(),
+         Action::SubmitCommand => "Run command".to_string(),
+         Action::CompleteCommand => "Complete".to_string(),
+         Action::NextCompletion => "Next completion".to_string(),
+         Action::PrevCompletion => "Previous completion".to_string(),
+         Action::NextGroup => "Next group".to_string(),
+         Action::PrevGroup => "Previous group".to_string(),
+         Action::SelectNext => "Next task".to_string(),
+         Action::SelectPrev => "Previous task".to_string(),
+         Action::SelectFirst => "First task".to_string(),
+         Action::SelectLast => "Last task".to_string(),
+         Action::OpenSelection => "Show details".to_string(),
+         Action::ClearSearch => "Clear search".to_string(),
+         Action::CycleSort => "Sort by next column".to_string(),
+         Action::ReverseSort => "Reverse sort".to_string(),
+         Action::ApplyFilter => "Apply filter".to_string(),
+         Action::CancelFilter => "Cancel filter".to_string(),
+         Action::ToggleDetailView => "Output/environment".to_string(),
+         Action::ScrollUp => "Scroll up".to_string(),
+         Action::ScrollDown => "Scroll down".to_string(),
+         Action::ScrollPageUp => "Page up".to_string(),
+         Action::ScrollPageDown => "Page down".to_string(),
+         Action::ScrollTop => "Scroll to top".to_string(),
+         Action::ScrollBottom => "Scroll to bottom".to_string(),
+         Action::ToggleMark => "Mark task".to_string(),
+         Action::ToggleVisualMark => "Mark range".to_string(),
+         Action::ToggleMarkAll => "Mark all".to_string(),
+         Action::ClearMarks => "Unmark all".to_string(),
+         Action::ApplyToSelection(task_action) => {
+            let name = task_action.as_str();
+            name[..1].to_uppercase() + &name[1..]
+         }
+         Action::NewTask => "Add task".to_string(),
+         Action::SubmitAddTask => "Add".to_string(),
+         Action::NextField => "Next field".to_string(),
+         Action::PrevField => "Previous field".to_string(),
+         Action::EditSelection => "Edit task".to_string(),
+         Action::Clean => "Clean up group".to_string(),
+         Action::Reset => "Reset group".to_string(),
+         Action::Shutdown => "Shut down daemon".to_string(),
+         Action::ConfirmAccept => "Yes".to_string(),
+         Action::ConfirmReject => "No".to_string(),
+         Action::ConfirmToggle => "Switch answer".to_string(),
+         Action::ConfirmSubmit => "Answer".to_string(),
+         action => format!("{action:?}"),
+      }
+   }
+
+   /// Whether a count typed before the key binding repeats the action, like `5j`.
+   pub const fn is_repeatable(&self) -> bool {
+      matches!(
+         self,
+         Action::NextGroup
+            | Action::PrevGroup
+            | Action::SelectNext
+            | Action::SelectPrev
+            | Action::ScrollUp
+            | Action::ScrollDown
+            | Action::ScrollPageUp
+            | Action::ScrollPageDown
+            | Action::ToggleMark
+            | Action::NextField
+            | Action::PrevField
+            | Action::NextCompletion
+            | Action::PrevCompletion
+      )
+   }
+}
+
+/// The groups of key bindings in the help, in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+   General,
+   Navigation,
+   Search,
+   Marks,
+   Tasks,
+   Daemon,
+}
+
+impl Category {
+   pub const fn as_str(&self) -> &str {
+      match self {
+         Category::General => "General",
+         Category::Navigation => "Navigation",
+         Category::Search => "Search & filter",
+         Category::Marks => "Marks",
+         Category::Tasks => "Tasks",
+         Category::Daemon => "Groups & daemon",
+      }
+   }
+}
+
+/// An action which only runs once the user agrees to the message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirmation {
+   pub title: String,
+   pub message: String,
+   pub action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskAction {
+   Start,
+   Pause,
+   Kill,
+   /// Restart finished tasks, reusing their ids.
+   Restart,
+   /// Restart finished tasks as new tasks.
+   RestartAsNew,
+   Stash,
+   Enqueue,
+   Remove,
+}
+
+impl TaskAction {
+   pub const fn as_str(&self) -> &str {
+      match self {
+         TaskAction::Start => "start",
+         TaskAction::Pause => "pause",
+         TaskAction::Kill => "kill",
+         TaskAction::Restart => "restart",
+         TaskAction::RestartAsNew => "restart as new",
+         TaskAction::Stash => "stash",
+         TaskAction::Enqueue => "enqueue",
+         TaskAction::Remove => "remove",
+      }
+   }
 }
@@ -1,65 +1,130 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+   collections::HashMap,
+   time::{Duration, Instant},
+};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::KeyEvent;
+use pueue_lib::{Task, message::AddRequest};
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
-use tokio::{sync::mpsc, time::sleep};
+use tokio::{
+   sync::mpsc::{self, UnboundedSender},
+   time::sleep_until,
+};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-   action::Action,
+   action::{Action, TaskAction},
    cli::CliArgs,
-   client::Client,
-   components::{Component, home::Home},
+   client::{Client, ClientError},
+   components::{
+      Component, add_task::AddTask, command_line::CommandLine, confirm::Confirm, detail::Detail,
+      help::Help, home::Home, notifications::Notifications, which_key::WhichKey,
+   },
+   config::Config,
+   editor,
+   keymap::{KeySequenceMatcher, KeyTrie},
+   poller::{PollHint, StatusPoller},
    tui::{Event, Tui, TuiConfig},
 };
 
 pub struct App {
-   status_reload_rate: f64,
    components: Vec<Box<dyn Component>>,
    should_quit: bool,
    mode: Mode,
-   last_tick_key_events: Vec<KeyEvent>,
    action_tx: mpsc::UnboundedSender<Action>,
    action_rx: mpsc::UnboundedReceiver<Action>,
-   keymaps: HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>,
+   /// Tells the status poller about the user, the receiver is taken when the poller starts.
+   poll_tx: mpsc::UnboundedSender<PollHint>,
+   poll_rx: Option<mpsc::UnboundedReceiver<PollHint>>,
+   keymaps: HashMap<Mode, KeyTrie>,
+   key_matcher: KeySequenceMatcher,
+   pending_keys: (Option<usize>, Vec<KeyEvent>),
    client: Client,
    tui_config: TuiConfig,
+   follow_token: Option<CancellationToken>,
+   config: Config,
+   /// The shortest time between two frames.
+   frame_interval: Duration,
+   last_draw: Instant,
+}
+
+/// Why the main loop woke up.
+enum Wake {
+   Event(Option<Event>),
+   Action(Option<Action>),
+   /// A redraw or a key timeout is due.
+   Deadline,
 }
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
    #[default]
    Home,
+   Detail,
+   Search,
+   Filter,
+   AddTask,
+   Confirm,
+   Help,
+   Command,
+   Messages,
+}
+
+impl Mode {
+   /// Whether typed characters go into a text input in this mode.
+   pub const fn has_text_input(&self) -> bool {
+      matches!(
+         self,
+         Mode::Search | Mode::Filter | Mode::AddTask | Mode::Command
+      )
+   }
+
+   /// Whether the mode is an overlay which can be opened above the detail view.
+   pub const fn is_overlay(&self) -> bool {
+      matches!(self, Mode::Help | Mode::Messages)
+   }
 }
 
 impl App {
    pub async fn new(opt: &CliArgs) -> color_eyre::Result<Self> {
       let (action_tx, action_rx) = mpsc::unbounded_channel();
+      let (poll_tx, poll_rx) = mpsc::unbounded_channel();
+      let config = Config::load(opt.tui_config.as_deref())?;
       Ok(Self {
-         status_reload_rate: 1.0,
-         components: vec![Box::new(Home::new())],
+         components: vec![
+            Box::new(Home::new()),
+            Box::new(Detail::new()),
+            Box::new(AddTask::new()),
+            Box::new(CommandLine::new()),
+            Box::new(WhichKey::new()),
+            Box::new(Confirm::new()),
+            Box::new(Help::new()),
+            Box::new(Notifications::new()),
+         ],
          should_quit: false,
          mode: Mode::Home,
-         last_tick_key_events: Vec::new(),
          action_tx,
          action_rx,
-         keymaps: {
-            let mut map = HashMap::new();
-
-            let mut home = HashMap::new();
-            home.insert(parse_key_sequence("<q>").unwrap(), Action::Quit);
-            home.insert(parse_key_sequence("<Ctrl-d>").unwrap(), Action::Quit);
-
-            map.insert(Mode::Home, home);
-            map
-         },
+         poll_tx,
+         poll_rx: Some(poll_rx),
+         keymaps: config
+            .keymaps()?
+            .iter()
+            .map(|(mode, keymap)| (*mode, KeyTrie::new(keymap)))
+            .collect(),
+         key_matcher: KeySequenceMatcher::new(Duration::from_secs_f64(config.key_timeout)),
+         pending_keys: (None, Vec::new()),
          client: Client::new(&opt.config, &opt.profile).await?,
          tui_config: TuiConfig {
-            frame_rate: 60.0,
-            tick_rate: 4.0,
-            mouse: true,
-            paste: false,
+            tick_rate: config.tick_rate,
+            mouse: config.mouse,
+            paste: config.paste,
          },
+         follow_token: None,
+         frame_interval: Duration::from_secs_f64(1.0 / config.frame_rate),
+         config,
+         last_draw: Instant::now(),
       })
    }
 
@@ -69,57 +134,89 @@ impl App {
       let mut tui = Tui::try_from(&self.tui_config)?;
       tui.enter()?;
 
-      let status_action_tx = self.action_tx.clone();
-      let status_client = self.client.clone();
-      let status_reload_duration = Duration::from_secs_f64(self.status_reload_rate);
-      tokio::spawn(async move {
-         loop {
-            match status_client.status().await {
-               Ok(state) => {
-                  let _ = status_action_tx.send(Action::UpdateStatus(state));
-               }
-               Err(e) => {
-                  let _ = status_action_tx
-                     .send(Action::Error(format!("Failed to fetch status: {:?}", e)));
-               }
-            }
-            sleep(status_reload_duration).await;
-         }
-      });
+      let poller = StatusPoller::new(
+         self.client.clone(),
+         self.action_tx.clone(),
+         Duration::from_secs_f64(self.config.status_reload_rate),
+         Duration::from_secs_f64(self.config.idle_status_reload_rate),
+      );
+      if let Some(poll_rx) = self.poll_rx.take() {
+         tokio::spawn(poller.run(poll_rx));
+      }
 
       for component in self.components.iter_mut() {
          component.register_action_handler(self.action_tx.clone())?;
       }
+      for component in self.components.iter_mut() {
+         component.register_config_handler(self.config.clone())?;
+      }
       for component in self.components.iter_mut() {
          component.init(tui.size()?)?;
       }
+      self.render(&mut tui)?;
 
       loop {
-         self.handle_events(&mut tui).await?;
-         self.handle_actions(&mut tui)?;
+         match self.wait(&mut tui).await {
+            Wake::Event(Some(event)) => self.handle_event(event)?,
+            Wake::Action(Some(action)) => self.handle_action(&mut tui, action).await?,
+            _ => {}
+         }
+         self.handle_key_timeout()?;
+         self.handle_actions(&mut tui).await?;
          if self.should_quit {
             tui.stop()?;
             break;
          }
+         if self
+            .next_redraw()
+            .is_some_and(|redraw| redraw <= Instant::now())
+         {
+            self.render(&mut tui)?;
+         }
       }
       tui.exit()?;
       Ok(())
    }
 
-   async fn handle_events(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
-      let Some(event) = tui.next_event().await else {
-         return Ok(());
-      };
+   /// Wait for the next event or action, or until a redraw or a key timeout is due.
+   async fn wait(&mut self, tui: &mut Tui) -> Wake {
+      let deadline = [self.next_redraw(), self.key_matcher.deadline()]
+         .into_iter()
+         .flatten()
+         .min();
+      tokio::select! {
+         event = tui.next_event() => Wake::Event(event),
+         action = self.action_rx.recv() => Wake::Action(action),
+         _ = sleep_until(deadline.unwrap_or_else(Instant::now).into()), if deadline.is_some() => {
+            Wake::Deadline
+         }
+      }
+   }
+
+   fn handle_event(&mut self, event: Event) -> color_eyre::Result<()> {
       let action_tx = self.action_tx.clone();
       match event {
          Event::Quit => action_tx.send(Action::Quit)?,
          Event::Tick => action_tx.send(Action::Tick)?,
-         Event::Render => action_tx.send(Action::Render)?,
          Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-         Event::Key(key) => self.handle_key_event(key)?,
+         Event::Key(key) => {
+            let _ = self.poll_tx.send(PollHint::Activity);
+            self.handle_key_event(key)?;
+         }
+         Event::FocusGained => {
+            let _ = self.poll_tx.send(PollHint::Focus(true));
+         }
+         Event::FocusLost => {
+            let _ = self.poll_tx.send(PollHint::Focus(false));
+         }
          _ => {}
       }
-      for component in self.components.iter_mut() {
+      // An open modal takes the input away from the components below it.
+      let modal = self.components.iter().rposition(|c| c.is_modal());
+      for (i, component) in self.components.iter_mut().enumerate() {
+         if matches!(event, Event::Key(_) | Event::Mouse(_)) && modal.is_some_and(|m| m != i) {
+            continue;
+         }
          if let Some(action) = component.handle_events(Some(event.clone()))? {
             action_tx.send(action)?;
          }
@@ -128,246 +225,324 @@ impl App {
    }
 
    fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<()> {
-      let action_tx = self.action_tx.clone();
       let Some(keymap) = self.keymaps.get(&self.mode) else {
          return Ok(());
       };
-      match keymap.get(&vec![key]) {
-         Some(action) => {
-            action_tx.send(action.clone())?;
-         }
-         _ => {
-            // If the key was not handled as a single key action,
-            // then consider it for multi-key combinations.
-            self.last_tick_key_events.push(key);
-
-            // Check for multi-key combinations
-            if let Some(action) = keymap.get(&self.last_tick_key_events) {
-               action_tx.send(action.clone())?;
-            }
-         }
+      let actions = self
+         .key_matcher
+         .push(keymap, key, !self.mode.has_text_input(), Instant::now());
+      for (action, count) in actions {
+         self.send_repeated(action, count)?;
+      }
+      self.update_pending_keys()
+   }
+
+   /// Run the binding of a pending key sequence once no further key has been typed in time.
+   fn handle_key_timeout(&mut self) -> color_eyre::Result<()> {
+      let Some(keymap) = self.keymaps.get(&self.mode) else {
+         return Ok(());
+      };
+      if let Some((action, count)) = self.key_matcher.check_timeout(keymap, Instant::now()) {
+         self.send_repeated(action, count)?;
+      }
+      self.update_pending_keys()
+   }
+
+   fn update_pending_keys(&mut self) -> color_eyre::Result<()> {
+      let pending_keys = (
+         self.key_matcher.count(),
+         self.key_matcher.pending().to_vec(),
+      );
+      if pending_keys != self.pending_keys {
+         self.pending_keys = pending_keys.clone();
+         let (count, keys) = pending_keys;
+         self.action_tx.send(Action::PendingKeys { count, keys })?;
       }
       Ok(())
    }
 
-   fn handle_actions(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
-      while let Ok(action) = self.action_rx.try_recv() {
-         match action {
-            Action::Tick => {
-               self.last_tick_key_events.drain(..);
-            }
-            Action::Quit => self.should_quit = true,
-            Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
-            Action::Render => self.render(tui)?,
-            _ => {}
-         }
-         for component in self.components.iter_mut() {
-            if let Some(action) = component.update(action.clone())? {
-               self.action_tx.send(action)?
-            };
-         }
+   fn send_repeated(&self, action: Action, count: usize) -> color_eyre::Result<()> {
+      let count = if action.is_repeatable() { count } else { 1 };
+      for _ in 1..count {
+         self.action_tx.send(action.clone())?;
       }
+      self.action_tx.send(action)?;
       Ok(())
    }
 
-   fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> color_eyre::Result<()> {
-      tui.resize(Rect::new(0, 0, w, h))?;
-      self.render(tui)?;
+   async fn handle_actions(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
+      while let Ok(action) = self.action_rx.try_recv() {
+         self.handle_action(tui, action).await?;
+      }
       Ok(())
    }
 
-   fn render(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
-      tui.draw(|frame| {
-         for component in self.components.iter_mut() {
-            if let Err(err) = component.draw(frame, frame.area()) {
-               let _ = self
-                  .action_tx
-                  .send(Action::Error(format!("Failed to draw: {:?}", err)));
+   async fn handle_action(&mut self, tui: &mut Tui, action: Action) -> color_eyre::Result<()> {
+      match action {
+         Action::Quit => self.should_quit = true,
+         Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
+         Action::SwitchMode(mode) => {
+            if mode != Mode::Detail && !mode.is_overlay() {
+               self.stop_following();
+            }
+            if mode != self.mode {
+               self.key_matcher.reset();
+               self.update_pending_keys()?;
             }
+            self.mode = mode;
          }
-      })?;
+         Action::ApplyToTasks(task_action, ref tasks) => {
+            self.run_task_action(task_action, tasks.clone())
+         }
+         Action::AddTask(ref request) => self.add_task(*request.clone()),
+         Action::CleanGroup(ref group) => {
+            let (client, group) = (self.client.clone(), group.clone());
+            self.spawn_request("clean up", async move { client.clean(group).await });
+         }
+         Action::AddGroup { ref name, parallel } => {
+            let (client, name) = (self.client.clone(), name.clone());
+            self.spawn_request("add the group", async move {
+               client.add_group(name, parallel).await
+            });
+         }
+         Action::RemoveGroup(ref name) => {
+            let (client, name) = (self.client.clone(), name.clone());
+            self.spawn_request("remove the group", async move {
+               client.remove_group(name).await
+            });
+         }
+         Action::SetParallel {
+            ref group,
+            parallel,
+         } => {
+            let (client, group) = (self.client.clone(), group.clone());
+            self.spawn_request("set the parallel tasks", async move {
+               client.set_parallel(group, parallel).await
+            });
+         }
+         Action::ResetGroup(ref group) => {
+            let (client, group) = (self.client.clone(), group.clone());
+            self.spawn_request("reset", async move { client.reset(group).await });
+         }
+         Action::ShutdownDaemon => {
+            let client = self.client.clone();
+            self.spawn_request(
+               "shut down the daemon",
+               async move { client.shutdown().await },
+            );
+         }
+         Action::EditTask(task_id) => self.edit_task(tui, task_id).await?,
+         Action::OpenDetail(task_id) => {
+            self.open_log(task_id);
+            self.action_tx.send(Action::SwitchMode(Mode::Detail))?;
+         }
+         _ => {}
+      }
+      for component in self.components.iter_mut() {
+         if let Some(action) = component.update(action.clone())? {
+            self.action_tx.send(action)?
+         };
+      }
       Ok(())
    }
 
-   fn set_panic_hook(&self) {
-      let hook = std::panic::take_hook();
-      let tui_config = self.tui_config.clone();
-      std::panic::set_hook(Box::new(move |info| {
-         if let Ok(mut t) = Tui::try_from(&tui_config) {
-            let _ = t.exit();
-         }
-         hook(info);
-      }));
+   fn run_task_action(&self, task_action: TaskAction, tasks: Vec<Task>) {
+      let action_tx = self.action_tx.clone();
+      let poll_tx = self.poll_tx.clone();
+      let client = self.client.clone();
+      tokio::spawn(async move {
+         let task_ids = tasks.iter().map(|task| task.id).collect();
+         let result = match task_action {
+            TaskAction::Start => client.start(task_ids).await,
+            TaskAction::Pause => client.pause(task_ids).await,
+            TaskAction::Kill => client.kill(task_ids).await,
+            TaskAction::Restart => client.restart(&tasks).await,
+            TaskAction::RestartAsNew => client.restart_as_new(&tasks).await,
+            TaskAction::Stash => client.stash(task_ids).await,
+            TaskAction::Enqueue => client.enqueue(task_ids).await,
+            TaskAction::Remove => client.remove(task_ids).await,
+         };
+         let _ = match result {
+            Ok(message) => action_tx.send(Action::Info(message)),
+            Err(e) => action_tx.send(Action::Error(format!(
+               "Failed to {}: {}",
+               task_action.as_str(),
+               e
+            ))),
+         };
+         let _ = poll_tx.send(PollHint::Refresh);
+      });
    }
-}
 
-fn parse_key_event(raw: &str) -> color_eyre::Result<KeyEvent, String> {
-   let raw_lower = raw.to_ascii_lowercase();
-   let (remaining, modifiers) = extract_modifiers(&raw_lower);
-   parse_key_code_with_modifiers(remaining, modifiers)
-}
+   fn add_task(&self, request: AddRequest) {
+      let action_tx = self.action_tx.clone();
+      let poll_tx = self.poll_tx.clone();
+      let client = self.client.clone();
+      tokio::spawn(async move {
+         let _ = match client.add(request).await {
+            Ok(task_id) => action_tx
+               .send(Action::Info(format!("New task added (id {task_id}).")))
+               .and_then(|_| action_tx.send(Action::SwitchMode(Mode::Home)))
+               .and_then(|_| action_tx.send(Action::SelectTask(task_id))),
+            Err(e) => action_tx.send(Action::AddTaskFailed(format!("Failed to add task: {}", e))),
+         };
+         let _ = poll_tx.send(PollHint::Refresh);
+      });
+   }
 
-fn extract_modifiers(raw: &str) -> (&str, KeyModifiers) {
-   let mut modifiers = KeyModifiers::empty();
-   let mut current = raw;
+   /// Send a request in the background and report its result.
+   fn spawn_request(
+      &self,
+      description: &'static str,
+      request: impl Future<Output = Result<String, ClientError>> + Send + 'static,
+   ) {
+      let action_tx = self.action_tx.clone();
+      let poll_tx = self.poll_tx.clone();
+      tokio::spawn(async move {
+         let _ = match request.await {
+            Ok(message) => action_tx.send(Action::Info(message)),
+            Err(e) => action_tx.send(Action::Error(format!("Failed to {description}: {e}"))),
+         };
+         let _ = poll_tx.send(PollHint::Refresh);
+      });
+   }
 
-   loop {
-      match current {
-         rest if rest.starts_with("ctrl-") => {
-            modifiers.insert(KeyModifiers::CONTROL);
-            current = &rest[5..];
+   /// Suspend the TUI while the task is edited in `$EDITOR`, then send the result to the daemon.
+   ///
+   /// The daemon locks the task until the edit is submitted or restored, so the lock is always
+   /// released, even if the editor fails.
+   async fn edit_task(&mut self, tui: &mut Tui, task_id: usize) -> color_eyre::Result<()> {
+      let task = match self.client.edit(task_id).await {
+         Ok(task) => task,
+         Err(e) => {
+            self
+               .action_tx
+               .send(Action::Error(format!("Failed to edit task {task_id}: {e}")))?;
+            return Ok(());
          }
-         rest if rest.starts_with("alt-") => {
-            modifiers.insert(KeyModifiers::ALT);
-            current = &rest[4..];
+      };
+
+      tui.exit()?;
+      let edited = editor::edit_task(&task).await;
+      tui.enter()?;
+      tui.clear()?;
+      self.render(tui)?;
+
+      let result = match edited {
+         Ok(Some(edited)) if edited != task => {
+            self.client.submit_edit(edited).await.map_err(Into::into)
          }
-         rest if rest.starts_with("shift-") => {
-            modifiers.insert(KeyModifiers::SHIFT);
-            current = &rest[6..];
+         Ok(_) => self
+            .client
+            .restore_edit(task_id)
+            .await
+            .map(|_| format!("Task {task_id} is unchanged."))
+            .map_err(Into::into),
+         Err(e) => {
+            let _ = self.client.restore_edit(task_id).await;
+            Err(e)
          }
-         _ => break, // break out of the loop if no known prefix is detected
       };
+      let _ = self.poll_tx.send(PollHint::Refresh);
+      self.action_tx.send(match result {
+         Ok(message) => Action::Info(message),
+         Err(e) => Action::Error(format!("Failed to edit task {task_id}: {e}")),
+      })?;
+      Ok(())
    }
 
-   (current, modifiers)
-}
+   fn open_log(&mut self, task_id: usize) {
+      self.stop_following();
+      let cancellation_token = CancellationToken::new();
+      self.follow_token = Some(cancellation_token.clone());
 
-fn parse_key_code_with_modifiers(
-   raw: &str,
-   mut modifiers: KeyModifiers,
-) -> color_eyre::Result<KeyEvent, String> {
-   let c = match raw {
-      "esc" => KeyCode::Esc,
-      "enter" => KeyCode::Enter,
-      "left" => KeyCode::Left,
-      "right" => KeyCode::Right,
-      "up" => KeyCode::Up,
-      "down" => KeyCode::Down,
-      "home" => KeyCode::Home,
-      "end" => KeyCode::End,
-      "pageup" => KeyCode::PageUp,
-      "pagedown" => KeyCode::PageDown,
-      "backtab" => {
-         modifiers.insert(KeyModifiers::SHIFT);
-         KeyCode::BackTab
-      }
-      "backspace" => KeyCode::Backspace,
-      "delete" => KeyCode::Delete,
-      "insert" => KeyCode::Insert,
-      "f1" => KeyCode::F(1),
-      "f2" => KeyCode::F(2),
-      "f3" => KeyCode::F(3),
-      "f4" => KeyCode::F(4),
-      "f5" => KeyCode::F(5),
-      "f6" => KeyCode::F(6),
-      "f7" => KeyCode::F(7),
-      "f8" => KeyCode::F(8),
-      "f9" => KeyCode::F(9),
-      "f10" => KeyCode::F(10),
-      "f11" => KeyCode::F(11),
-      "f12" => KeyCode::F(12),
-      "space" => KeyCode::Char(' '),
-      "hyphen" => KeyCode::Char('-'),
-      "minus" => KeyCode::Char('-'),
-      "tab" => KeyCode::Tab,
-      c if c.len() == 1 => {
-         let mut c = c.chars().next().unwrap();
-         if modifiers.contains(KeyModifiers::SHIFT) {
-            c = c.to_ascii_uppercase();
+      let action_tx = self.action_tx.clone();
+      let client = self.client.clone();
+      tokio::spawn(async move {
+         tokio::select! {
+            _ = cancellation_token.cancelled() => {}
+            result = Self::follow_log(&client, &action_tx, task_id) => {
+               if let Err(e) = result {
+                  let _ = action_tx.send(Action::Error(format!("Failed to fetch log: {:?}", e)));
+               }
+            }
          }
-         KeyCode::Char(c)
-      }
-      _ => return Err(format!("Unable to parse {raw}")),
-   };
-   Ok(KeyEvent::new(c, modifiers))
-}
+      });
+   }
 
-fn key_event_to_string(key_event: &KeyEvent) -> String {
-   let char;
-   let key_code = match key_event.code {
-      KeyCode::Backspace => "backspace",
-      KeyCode::Enter => "enter",
-      KeyCode::Left => "left",
-      KeyCode::Right => "right",
-      KeyCode::Up => "up",
-      KeyCode::Down => "down",
-      KeyCode::Home => "home",
-      KeyCode::End => "end",
-      KeyCode::PageUp => "pageup",
-      KeyCode::PageDown => "pagedown",
-      KeyCode::Tab => "tab",
-      KeyCode::BackTab => "backtab",
-      KeyCode::Delete => "delete",
-      KeyCode::Insert => "insert",
-      KeyCode::F(c) => {
-         char = format!("f({c})");
-         &char
+   /// Fetch the log of a task and keep streaming its output while it is running.
+   async fn follow_log(
+      client: &Client,
+      action_tx: &UnboundedSender<Action>,
+      task_id: usize,
+   ) -> color_eyre::Result<()> {
+      let mut log = client.log(task_id).await?;
+      if !log.task.is_running() {
+         action_tx.send(Action::UpdateLog(Box::new(log)))?;
+         return Ok(());
       }
-      KeyCode::Char(' ') => "space",
-      KeyCode::Char(c) => {
-         char = c.to_string();
-         &char
+
+      // The stream starts from the beginning of the output, so drop the snapshot.
+      log.output.clear();
+      log.output_complete = true;
+      action_tx.send(Action::UpdateLog(Box::new(log)))?;
+
+      let mut stream = client.follow(task_id).await?;
+      while let Some(chunk) = stream.next().await? {
+         action_tx.send(Action::AppendLog(task_id, chunk))?;
       }
-      KeyCode::Esc => "esc",
-      KeyCode::Null => "",
-      KeyCode::CapsLock => "",
-      KeyCode::Menu => "",
-      KeyCode::ScrollLock => "",
-      KeyCode::Media(_) => "",
-      KeyCode::NumLock => "",
-      KeyCode::PrintScreen => "",
-      KeyCode::Pause => "",
-      KeyCode::KeypadBegin => "",
-      KeyCode::Modifier(_) => "",
-   };
-
-   let mut modifiers = Vec::with_capacity(3);
-
-   if key_event.modifiers.intersects(KeyModifiers::CONTROL) {
-      modifiers.push("ctrl");
+      Ok(())
    }
 
-   if key_event.modifiers.intersects(KeyModifiers::SHIFT) {
-      modifiers.push("shift");
+   fn stop_following(&mut self) {
+      if let Some(token) = self.follow_token.take() {
+         token.cancel();
+      }
    }
 
-   if key_event.modifiers.intersects(KeyModifiers::ALT) {
-      modifiers.push("alt");
+   fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> color_eyre::Result<()> {
+      tui.resize(Rect::new(0, 0, w, h))?;
+      self.render(tui)?;
+      Ok(())
    }
 
-   let mut key = modifiers.join("-");
-
-   if !key.is_empty() {
-      key.push('-');
+   /// When the screen has to be drawn next, as soon as the frame rate allows if anything changed.
+   fn next_redraw(&self) -> Option<Instant> {
+      if self.components.iter().any(|c| c.is_dirty()) {
+         return Some(self.last_draw + self.frame_interval);
+      }
+      self
+         .components
+         .iter()
+         .filter_map(|c| c.next_redraw(self.last_draw))
+         .min()
    }
-   key.push_str(key_code);
 
-   key
-}
-
-fn parse_key_sequence(raw: &str) -> color_eyre::Result<Vec<KeyEvent>, String> {
-   if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
-      return Err(format!("Unable to parse `{}`", raw));
-   }
-   let raw = if !raw.contains("><") {
-      let raw = raw.strip_prefix('<').unwrap_or(raw);
-      let raw = raw.strip_prefix('>').unwrap_or(raw);
-      raw
-   } else {
-      raw
-   };
-   let sequences = raw
-      .split("><")
-      .map(|seq| {
-         if let Some(s) = seq.strip_prefix('<') {
-            s
-         } else if let Some(s) = seq.strip_suffix('>') {
-            s
-         } else {
-            seq
+   fn render(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
+      self.last_draw = Instant::now();
+      tui.draw(|frame| {
+         // Modals are layered above the other components.
+         let (modals, others): (Vec<_>, Vec<_>) =
+            self.components.iter_mut().partition(|c| c.is_modal());
+         for component in others.into_iter().chain(modals) {
+            if let Err(err) = component.draw(frame, frame.area()) {
+               let _ = self
+                  .action_tx
+                  .send(Action::Error(format!("Failed to draw: {:?}", err)));
+            }
          }
-      })
-      .collect::<Vec<_>>();
+      })?;
+      Ok(())
+   }
 
-   sequences.into_iter().map(parse_key_event).collect()
+   fn set_panic_hook(&self) {
+      let hook = std::panic::take_hook();
+      let tui_config = self.tui_config.clone();
+      std::panic::set_hook(Box::new(move |info| {
+         if let Ok(mut t) = Tui::try_from(&tui_config) {
+            let _ = t.exit();
+         }
+         hook(info);
+      }));
+   }
 }
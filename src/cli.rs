@@ -15,6 +15,13 @@ pub struct CliArgs {
    /// The name of the profile that should be loaded from your config file.
    #[arg(short, long)]
    pub profile: Option<String>,
+
+   /// Path to the pueue-tui config file.
+   ///
+   /// Defaults to "pueue-tui/config.yml" in your config directory, e.g. "~/.config".
+   /// This path can also be set via the "PUEUE_TUI_CONFIG" environment variable.
+   #[arg(long, env = "PUEUE_TUI_CONFIG", value_hint = ValueHint::FilePath)]
+   pub tui_config: Option<PathBuf>,
 }
 
 impl CliArgs {
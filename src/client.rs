@@ -1,54 +1,509 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{fmt, io::Read, path::PathBuf, sync::Arc};
 use tokio::sync::Mutex;
 
-use color_eyre::eyre::{WrapErr, bail};
 use pueue_lib::{
-   Request, Response, Settings, State,
+   Request, Response, Settings, State, Task,
+   message::{
+      AddRequest, CleanRequest, EditableTask, EnqueueRequest, GroupRequest, KillRequest,
+      LogRequest, ParallelRequest, PauseRequest, ResetRequest, ResetTarget, RestartRequest,
+      ShutdownRequest, StartRequest, StashRequest, StreamRequest, TaskLogResponse, TaskSelection,
+      TaskToRestart,
+   },
    network::{self, socket::ConnectionSettings},
    secret::read_shared_secret,
+   settings::Shared,
 };
+use serde::{Deserialize, Serialize};
+use snap::read::FrameDecoder;
+
+/// Why a request to the daemon failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+   /// The pueue configuration can't be read or is invalid.
+   Config(String),
+   /// The daemon can't be reached, e.g. because it isn't running (yet).
+   Connection(String),
+   /// The daemon rejected the shared secret.
+   Authentication(PathBuf),
+   /// The daemon sent a message which this version of pueue-lib doesn't understand.
+   Protocol(String),
+   /// The daemon refused the request.
+   Daemon(String),
+}
+
+impl fmt::Display for ClientError {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      match self {
+         ClientError::Config(err) => write!(f, "Invalid pueue configuration: {err}"),
+         ClientError::Connection(err) => {
+            write!(f, "Can't connect to the daemon, is pueued running? {err}")
+         }
+         ClientError::Authentication(secret_path) => write!(
+            f,
+            "The daemon rejected the shared secret in {}, check `shared_secret_path` in the pueue configuration",
+            secret_path.display()
+         ),
+         ClientError::Protocol(err) => write!(
+            f,
+            "Unexpected message from the daemon, it may run a version incompatible with pueue-lib {}: {err}",
+            pueue_lib::PROTOCOL_VERSION
+         ),
+         ClientError::Daemon(message) => f.write_str(message),
+      }
+   }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<pueue_lib::Error> for ClientError {
+   fn from(err: pueue_lib::Error) -> Self {
+      use pueue_lib::Error;
+
+      match err {
+         Error::EmptyPayload
+         | Error::MessageDeserialization(_)
+         | Error::UnexpectedPayload(_)
+         | Error::MessageSerialization(_)
+         | Error::MessageTooBig(..) => ClientError::Protocol(single_line(&err.to_string())),
+         Error::InvalidPath(_) | Error::CertificateFailure(_) | Error::ConfigDeserialization(_) => {
+            ClientError::Config(single_line(&err.to_string()))
+         }
+         _ => ClientError::Connection(single_line(&err.to_string())),
+      }
+   }
+}
+
+impl ClientError {
+   fn unexpected(response: Response) -> Self {
+      let name = format!("{response:?}");
+      let name = name.split(['(', ' ', '{']).next().unwrap_or_default();
+      ClientError::Protocol(format!("Got an unexpected `{name}` response."))
+   }
+}
+
+/// Whether the daemon speaks a protocol compatible with the pueue-lib this TUI is built with.
+pub fn is_compatible_version(daemon_version: &str) -> bool {
+   versions_compatible(daemon_version, pueue_lib::PROTOCOL_VERSION)
+}
+
+/// Whether two versions are compatible by semver, e.g. `0.30.0` and `0.30.1` or `4.0` and `4.1`.
+fn versions_compatible(a: &str, b: &str) -> bool {
+   fn significant(version: &str) -> Option<(u64, Option<u64>)> {
+      let mut parts = version
+         .trim()
+         .trim_start_matches('v')
+         .split(['.', '-', '+']);
+      let major = parts.next()?.parse().ok()?;
+      let minor = parts.next()?.parse().ok()?;
+      Some(if major == 0 {
+         (major, Some(minor))
+      } else {
+         (major, None)
+      })
+   }
+
+   significant(a).is_some_and(|a| Some(a) == significant(b))
+}
+
+/// Join the lines of multi-line error messages, like pueue-lib's, so they fit into the status
+/// bar.
+pub fn single_line(message: &str) -> String {
+   message
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty())
+      .collect::<Vec<_>>()
+      .join(" ")
+}
 
 #[derive(Clone)]
 pub struct Client {
-   connection: Arc<Mutex<network::Client>>,
+   shared: Arc<Shared>,
+   /// The connection to the daemon, or `None` until it is (re-)established by the next request.
+   connection: Arc<Mutex<Option<network::Client>>>,
+   /// The protocol version the daemon reported when the connection was last established.
+   daemon_version: Arc<Mutex<Option<String>>>,
 }
 
 impl Client {
+   /// Read the settings of the daemon.
+   ///
+   /// The connection is established by the first request, so the daemon doesn't have to run yet.
    pub async fn new(
       config: &Option<PathBuf>,
       profile: &Option<String>,
-   ) -> color_eyre::Result<Self> {
-      let (mut settings, config_found) =
-         Settings::read(config).wrap_err("Failed to read configuration.")?;
+   ) -> Result<Self, ClientError> {
+      let (mut settings, _) = Settings::read(config).map_err(ClientError::from)?;
       if let Some(profile) = profile {
          settings.load_profile(profile)?;
       }
 
-      // Error if no configuration file can be found, as this is an indicator, that the daemon hasn't been started yet.
-      if !config_found {
-         bail!("Couldn't find a configuration file. Did you start the daemon yet?");
-      }
+      Ok(Self {
+         shared: Arc::new(settings.shared),
+         connection: Arc::new(Mutex::new(None)),
+         daemon_version: Arc::new(Mutex::new(None)),
+      })
+   }
 
-      let connection_settings = ConnectionSettings::try_from(settings.shared.clone())?;
-      let secret = read_shared_secret(&settings.shared.shared_secret_path())?;
-      let connection = network::Client::new(connection_settings, &secret, true)
+   async fn connect(shared: &Shared) -> Result<network::Client, ClientError> {
+      // The daemon creates the secret on its first start, so it is read on every connect.
+      let secret_path = shared.shared_secret_path();
+      let secret = read_shared_secret(&secret_path).map_err(|err| {
+         ClientError::Connection(format!("Failed to read the shared secret: {err}"))
+      })?;
+      let connection_settings = ConnectionSettings::try_from(shared.clone())?;
+      network::Client::new(connection_settings, &secret, false)
          .await
-         .context("Failed to initialize client.")?;
+         .map_err(|err| {
+            // pueue-lib only reports a rejected secret in the message of the handshake error.
+            if err
+               .chain()
+               .any(|cause| cause.to_string().contains("correct secret"))
+            {
+               ClientError::Authentication(secret_path)
+            } else {
+               ClientError::Connection(format!("{err:#}"))
+            }
+         })
+   }
 
-      Ok(Self {
-         connection: Arc::new(Mutex::new(connection)),
-      })
+   /// The protocol version of the daemon, known as soon as a connection has been established.
+   ///
+   /// It is kept when the request which connected fails, e.g. because the daemon speaks an
+   /// incompatible protocol.
+   pub async fn daemon_version(&self) -> Option<String> {
+      self.daemon_version.lock().await.clone()
    }
 
-   pub async fn status(&self) -> color_eyre::Result<State> {
+   /// Send a request over the shared connection, connecting first if necessary.
+   ///
+   /// The connection is dropped if it fails, so the next request reconnects.
+   async fn send(&self, request: impl Into<Request>) -> Result<Response, ClientError> {
       let mut connection = self.connection.lock().await;
-      connection.send_request(Request::Status).await?;
-      let response = connection.receive_response().await?;
+      if connection.is_none() {
+         let client = Self::connect(&self.shared).await?;
+         *self.daemon_version.lock().await = Some(client.daemon_version().clone());
+         *connection = Some(client);
+      }
+      let client = connection.as_mut().expect("connected above");
+      let response = match client.send_request(request.into()).await {
+         Ok(()) => client.receive_response().await,
+         Err(err) => Err(err),
+      };
+      if response.is_err() {
+         *connection = None;
+      }
+      Ok(response?)
+   }
+
+   /// Send a request that the daemon answers with a success or failure message.
+   async fn send_task_request(&self, request: impl Into<Request>) -> Result<String, ClientError> {
+      match self.send(request).await? {
+         Response::Success(message) => Ok(message),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+
+   pub async fn status(&self) -> Result<State, ClientError> {
+      let response = self.send(Request::Status).await?;
 
       match response {
          Response::Status(state) => Ok(*state),
-         _ => unreachable!(),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+
+   /// Add a new task and return its id.
+   pub async fn add(&self, request: AddRequest) -> Result<usize, ClientError> {
+      match self.send(request).await? {
+         Response::AddedTask(added) => Ok(added.task_id),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+
+   pub async fn log(&self, task_id: usize) -> Result<TaskLog, ClientError> {
+      let response = self
+         .send(LogRequest {
+            tasks: TaskSelection::TaskIds(vec![task_id]),
+            send_logs: true,
+            lines: None,
+         })
+         .await?;
+
+      match response {
+         Response::Log(mut logs) => logs
+            .remove(&task_id)
+            .ok_or_else(|| ClientError::Daemon(format!("Task {task_id} doesn't exist.")))?
+            .try_into(),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+
+   pub async fn start(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self
+         .send_task_request(StartRequest {
+            tasks: TaskSelection::TaskIds(task_ids),
+         })
+         .await
+   }
+
+   pub async fn pause(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self
+         .send_task_request(PauseRequest {
+            tasks: TaskSelection::TaskIds(task_ids),
+            wait: false,
+         })
+         .await
+   }
+
+   pub async fn kill(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self
+         .send_task_request(KillRequest {
+            tasks: TaskSelection::TaskIds(task_ids),
+            signal: None,
+         })
+         .await
+   }
+
+   /// Restart finished tasks in place, reusing their ids.
+   pub async fn restart(&self, tasks: &[Task]) -> Result<String, ClientError> {
+      self
+         .send_task_request(RestartRequest {
+            tasks: tasks
+               .iter()
+               .map(|task| TaskToRestart {
+                  task_id: task.id,
+                  original_command: task.original_command.clone(),
+                  path: task.path.clone(),
+                  label: task.label.clone(),
+                  priority: task.priority,
+               })
+               .collect(),
+            start_immediately: false,
+            stashed: false,
+         })
+         .await
+   }
+
+   /// Restart tasks by enqueueing a copy of each of them as a new task.
+   ///
+   /// The tasks are added one by one, so a failing task, even one whose request can't be sent,
+   /// doesn't prevent the others from being added. Failures are reported in the message like the
+   /// daemon does for other task requests.
+   pub async fn restart_as_new(&self, tasks: &[Task]) -> Result<String, ClientError> {
+      let mut new_ids = Vec::with_capacity(tasks.len());
+      let mut failures = Vec::new();
+      for task in tasks {
+         let response = self
+            .send(AddRequest {
+               command: task.original_command.clone(),
+               path: task.path.clone(),
+               envs: task.envs.clone(),
+               start_immediately: false,
+               stashed: false,
+               group: task.group.clone(),
+               enqueue_at: None,
+               dependencies: vec![],
+               priority: Some(task.priority),
+               label: task.label.clone(),
+            })
+            .await;
+         match response {
+            Ok(Response::AddedTask(added)) => new_ids.push(added.task_id.to_string()),
+            Ok(Response::Failure(message)) => failures.push(format!("{} ({})", task.id, message)),
+            Ok(response) => failures.push(format!(
+               "{} ({})",
+               task.id,
+               ClientError::unexpected(response)
+            )),
+            Err(err) => failures.push(format!("{} ({err})", task.id)),
+         }
+      }
+      let mut message = format!("New tasks added: {}", new_ids.join(", "));
+      if !failures.is_empty() {
+         let failures = format!("The command failed for tasks: {}", failures.join(", "));
+         if new_ids.is_empty() {
+            return Err(ClientError::Daemon(failures));
+         }
+         message = format!("{message}; {failures}");
       }
+      Ok(message)
+   }
+
+   pub async fn stash(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self
+         .send_task_request(StashRequest {
+            tasks: TaskSelection::TaskIds(task_ids),
+            enqueue_at: None,
+         })
+         .await
+   }
+
+   pub async fn enqueue(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self
+         .send_task_request(EnqueueRequest {
+            tasks: TaskSelection::TaskIds(task_ids),
+            enqueue_at: None,
+         })
+         .await
+   }
+
+   pub async fn remove(&self, task_ids: Vec<usize>) -> Result<String, ClientError> {
+      self.send_task_request(Request::Remove(task_ids)).await
+   }
+
+   /// Remove all finished tasks of a group, or of all groups.
+   pub async fn clean(&self, group: Option<String>) -> Result<String, ClientError> {
+      self
+         .send_task_request(CleanRequest {
+            successful_only: false,
+            group,
+         })
+         .await
+   }
+
+   /// Kill and remove all tasks of a group, or of all groups.
+   pub async fn reset(&self, group: Option<String>) -> Result<String, ClientError> {
+      let target = match group {
+         Some(group) => ResetTarget::Groups(vec![group]),
+         None => ResetTarget::All,
+      };
+      self.send_task_request(ResetRequest { target }).await
+   }
+
+   pub async fn shutdown(&self) -> Result<String, ClientError> {
+      self.send_task_request(ShutdownRequest::Graceful).await
+   }
+
+   pub async fn add_group(
+      &self,
+      name: String,
+      parallel_tasks: Option<usize>,
+   ) -> Result<String, ClientError> {
+      self
+         .send_task_request(GroupRequest::Add {
+            name,
+            parallel_tasks,
+         })
+         .await
+   }
+
+   pub async fn remove_group(&self, name: String) -> Result<String, ClientError> {
+      self.send_task_request(GroupRequest::Remove(name)).await
+   }
+
+   /// Set how many tasks of a group may run at the same time.
+   pub async fn set_parallel(
+      &self,
+      group: String,
+      parallel_tasks: usize,
+   ) -> Result<String, ClientError> {
+      self
+         .send_task_request(ParallelRequest {
+            parallel_tasks,
+            group,
+         })
+         .await
+   }
+
+   /// Lock a stashed or queued task for editing and return its editable fields.
+   ///
+   /// The task stays locked until [`Client::submit_edit`] or [`Client::restore_edit`] is called.
+   pub async fn edit(&self, task_id: usize) -> Result<EditableTask, ClientError> {
+      match self.send(Request::EditRequest(vec![task_id])).await? {
+         Response::Edit(mut tasks) if tasks.len() == 1 => Ok(tasks.remove(0)),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+
+   pub async fn submit_edit(&self, task: EditableTask) -> Result<String, ClientError> {
+      self
+         .send_task_request(Request::EditedTasks(vec![task]))
+         .await
+   }
+
+   /// Unlock a task without changing it.
+   pub async fn restore_edit(&self, task_id: usize) -> Result<String, ClientError> {
+      self
+         .send_task_request(Request::EditRestore(vec![task_id]))
+         .await
+   }
+
+   /// Start streaming the output of a task.
+   ///
+   /// Streaming blocks the connection until the task finishes, so it uses a dedicated
+   /// connection instead of the shared one.
+   pub async fn follow(&self, task_id: usize) -> Result<LogStream, ClientError> {
+      let mut connection = Self::connect(&self.shared).await?;
+      connection
+         .send_request(StreamRequest {
+            tasks: TaskSelection::TaskIds(vec![task_id]),
+            lines: None,
+         })
+         .await?;
+
+      Ok(LogStream {
+         task_id,
+         connection,
+      })
+   }
+}
+
+/// A live stream of a running task's output.
+pub struct LogStream {
+   task_id: usize,
+   connection: network::Client,
+}
+
+impl LogStream {
+   /// Wait for the next chunk of output.
+   ///
+   /// Returns `None` once the daemon closes the stream, e.g. because the task finished.
+   pub async fn next(&mut self) -> Result<Option<String>, ClientError> {
+      match self.connection.receive_response().await? {
+         Response::Stream(mut response) => Ok(Some(
+            response.logs.remove(&self.task_id).unwrap_or_default(),
+         )),
+         Response::Close => Ok(None),
+         Response::Failure(message) => Err(ClientError::Daemon(message)),
+         response => Err(ClientError::unexpected(response)),
+      }
+   }
+}
+
+/// A task together with its decompressed output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskLog {
+   pub task: Task,
+   pub output: String,
+   /// Whether the daemon sent the complete output, i.e. it wasn't truncated.
+   pub output_complete: bool,
+}
+
+impl TryFrom<TaskLogResponse> for TaskLog {
+   type Error = ClientError;
+
+   fn try_from(response: TaskLogResponse) -> Result<Self, Self::Error> {
+      // The daemon compresses the output with snap to save bandwidth.
+      let mut output = Vec::new();
+      if let Some(compressed) = response.output {
+         FrameDecoder::new(compressed.as_slice())
+            .read_to_end(&mut output)
+            .map_err(|err| {
+               ClientError::Protocol(format!("Failed to decompress task output: {err}"))
+            })?;
+      }
+
+      Ok(Self {
+         task: response.task,
+         output: String::from_utf8_lossy(&output).into_owned(),
+         output_complete: response.output_complete,
+      })
    }
 }
 
@@ -58,6 +513,7 @@ mod tests {
 
    use std::{fs, path::PathBuf};
 
+   use pretty_assertions::assert_eq;
    use tempfile::tempdir;
    use testcontainers::{
       ContainerAsync, GenericBuildableImage, GenericImage, ImageExt,
@@ -138,6 +594,39 @@ shared:
       Ok((container, config_path))
    }
 
+   #[test]
+   fn test_error_kinds() {
+      let err = ClientError::from(pueue_lib::Error::IoError(
+         "reading request size header".to_string(),
+         std::io::Error::from(std::io::ErrorKind::ConnectionReset),
+      ));
+      assert!(matches!(err, ClientError::Connection(_)));
+      assert!(!err.to_string().contains('\n'));
+
+      let err = ClientError::from(pueue_lib::Error::MessageDeserialization("x".to_string()));
+      assert!(matches!(err, ClientError::Protocol(_)));
+
+      let err = ClientError::unexpected(Response::Close);
+      assert_eq!(
+         err,
+         ClientError::Protocol("Got an unexpected `Close` response.".to_string())
+      );
+      assert_eq!(
+         ClientError::Daemon("Group doesn't exist".to_string()).to_string(),
+         "Group doesn't exist"
+      );
+   }
+
+   #[test]
+   fn test_versions_compatible() {
+      assert!(versions_compatible("0.30.0", "0.30.1"));
+      assert!(versions_compatible("4.0.0", "4.2.1"));
+      assert!(!versions_compatible("0.29.2", "0.30.1"));
+      assert!(!versions_compatible("3.4.1", "4.0.0"));
+      assert!(!versions_compatible("unknown", "0.30.1"));
+      assert!(is_compatible_version(pueue_lib::PROTOCOL_VERSION));
+   }
+
    #[tokio::test]
    #[ignore = "Requires Docker and is slow."]
    async fn test_client_initialization() {
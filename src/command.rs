@@ -0,0 +1,550 @@
+use crate::{
+   action::{Action, TaskAction},
+   filter::{KEYS, StatusFilter},
+   widgets::task_table::{HeaderCell, SortKey},
+};
+
+/// What a [`Command`] does, so parsing it is checked for every command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+   Add,
+   Start,
+   Pause,
+   Kill,
+   Restart,
+   Stash,
+   Enqueue,
+   Remove,
+   Edit,
+   Clean,
+   Reset,
+   Group,
+   Parallel,
+   Sort,
+   Filter,
+   Shutdown,
+   Help,
+   Quit,
+}
+
+/// A command of the command line, named like the `pueue` subcommand it mirrors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+   pub kind: CommandKind,
+   pub name: &'static str,
+   pub usage: &'static str,
+   pub description: &'static str,
+}
+
+impl Command {
+   /// Whether the command does something useful without any arguments.
+   pub fn takes_no_args(&self) -> bool {
+      !self.usage.contains('<')
+   }
+}
+
+pub const COMMANDS: [Command; 18] = [
+   Command {
+      kind: CommandKind::Add,
+      name: "add",
+      usage: "add",
+      description: "Open the form for a new task",
+   },
+   Command {
+      kind: CommandKind::Start,
+      name: "start",
+      usage: "start [ids]",
+      description: "Start or resume tasks",
+   },
+   Command {
+      kind: CommandKind::Pause,
+      name: "pause",
+      usage: "pause [ids]",
+      description: "Pause tasks",
+   },
+   Command {
+      kind: CommandKind::Kill,
+      name: "kill",
+      usage: "kill [ids]",
+      description: "Kill tasks",
+   },
+   Command {
+      kind: CommandKind::Restart,
+      name: "restart",
+      usage: "restart [-i] [ids]",
+      description: "Restart tasks as new tasks, or in place with -i",
+   },
+   Command {
+      kind: CommandKind::Stash,
+      name: "stash",
+      usage: "stash [ids]",
+      description: "Stash tasks",
+   },
+   Command {
+      kind: CommandKind::Enqueue,
+      name: "enqueue",
+      usage: "enqueue [ids]",
+      description: "Enqueue stashed tasks",
+   },
+   Command {
+      kind: CommandKind::Remove,
+      name: "remove",
+      usage: "remove [ids]",
+      description: "Remove tasks",
+   },
+   Command {
+      kind: CommandKind::Edit,
+      name: "edit",
+      usage: "edit [id]",
+      description: "Edit a stashed or queued task",
+   },
+   Command {
+      kind: CommandKind::Clean,
+      name: "clean",
+      usage: "clean",
+      description: "Remove the finished tasks of the group",
+   },
+   Command {
+      kind: CommandKind::Reset,
+      name: "reset",
+      usage: "reset",
+      description: "Kill and remove all tasks of the group",
+   },
+   Command {
+      kind: CommandKind::Group,
+      name: "group",
+      usage: "group add|remove <name> [-p <parallel>]",
+      description: "Add or remove a group",
+   },
+   Command {
+      kind: CommandKind::Parallel,
+      name: "parallel",
+      usage: "parallel <n> [-g <group>]",
+      description: "Set how many tasks of a group run at once",
+   },
+   Command {
+      kind: CommandKind::Sort,
+      name: "sort",
+      usage: "sort [<column> [asc|desc]]...",
+      description: "Sort the tasks, by id if no column is given",
+   },
+   Command {
+      kind: CommandKind::Filter,
+      name: "filter",
+      usage: "filter [query]",
+      description: "Filter the tasks, e.g. `status:failed`",
+   },
+   Command {
+      kind: CommandKind::Shutdown,
+      name: "shutdown",
+      usage: "shutdown",
+      description: "Shut down the daemon",
+   },
+   Command {
+      kind: CommandKind::Help,
+      name: "help",
+      usage: "help",
+      description: "Show the key bindings",
+   },
+   Command {
+      kind: CommandKind::Quit,
+      name: "quit",
+      usage: "quit",
+      description: "Quit pueue-tui",
+   },
+];
+
+/// Parse a command line like `kill 3 4` or `parallel 4 -g gpu` into the action it runs.
+pub fn parse(input: &str) -> Result<Action, String> {
+   let input = input.trim();
+   let (name, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+   if name.is_empty() {
+      return Err("Empty command".to_string());
+   }
+   let command = COMMANDS
+      .iter()
+      .find(|command| command.name == name)
+      .ok_or_else(|| format!("Unknown command `{name}`"))?;
+   // The filter has its own syntax and quotes, so the rest of the line is used as is.
+   let words = match command.kind {
+      CommandKind::Filter => Vec::new(),
+      _ => shlex::split(rest).ok_or_else(|| "Unbalanced quotes".to_string())?,
+   };
+   let mut args: Vec<&str> = words.iter().map(String::as_str).collect();
+
+   let action = match command.kind {
+      CommandKind::Add => Action::NewTask,
+      CommandKind::Start => task_action(TaskAction::Start, &args)?,
+      CommandKind::Pause => task_action(TaskAction::Pause, &args)?,
+      CommandKind::Kill => task_action(TaskAction::Kill, &args)?,
+      CommandKind::Restart => {
+         let restart = if take_flag(&mut args, &["-i", "--in-place"]) {
+            TaskAction::Restart
+         } else {
+            TaskAction::RestartAsNew
+         };
+         task_action(restart, &args)?
+      }
+      CommandKind::Stash => task_action(TaskAction::Stash, &args)?,
+      CommandKind::Enqueue => task_action(TaskAction::Enqueue, &args)?,
+      CommandKind::Remove => task_action(TaskAction::Remove, &args)?,
+      CommandKind::Edit => match args[..] {
+         [] => Action::EditSelection,
+         [id] => Action::EditTask(parse_id(id)?),
+         _ => return Err(usage(command)),
+      },
+      CommandKind::Clean
+      | CommandKind::Reset
+      | CommandKind::Shutdown
+      | CommandKind::Help
+      | CommandKind::Quit
+         if !args.is_empty() =>
+      {
+         return Err(usage(command));
+      }
+      CommandKind::Clean => Action::Clean,
+      CommandKind::Reset => Action::Reset,
+      CommandKind::Shutdown => Action::Shutdown,
+      CommandKind::Help => Action::OpenHelp,
+      CommandKind::Quit => Action::Quit,
+      CommandKind::Group => {
+         let parallel = take_option(&mut args, &["-p", "--parallel"])?
+            .map(parse_parallel)
+            .transpose()?;
+         match args[..] {
+            ["add", name] => Action::AddGroup {
+               name: name.to_string(),
+               parallel,
+            },
+            ["remove", name] if parallel.is_none() => Action::RemoveGroup(name.to_string()),
+            _ => return Err(usage(command)),
+         }
+      }
+      CommandKind::Parallel => {
+         let group = take_option(&mut args, &["-g", "--group"])?.unwrap_or("default");
+         match args[..] {
+            [parallel] => Action::SetParallel {
+               group: group.to_string(),
+               parallel: parse_parallel(parallel)?,
+            },
+            _ => return Err(usage(command)),
+         }
+      }
+      CommandKind::Sort => Action::SortTasks(parse_sort_keys(&args)?),
+      CommandKind::Filter => Action::SetFilter(rest.trim().to_string()),
+   };
+   Ok(action)
+}
+
+fn usage(command: &Command) -> String {
+   format!("Usage: {}", command.usage)
+}
+
+/// Apply the action to the given task ids, or to the selection if there are none.
+fn task_action(task_action: TaskAction, args: &[&str]) -> Result<Action, String> {
+   if args.is_empty() {
+      return Ok(Action::ApplyToSelection(task_action));
+   }
+   let ids = args
+      .iter()
+      .map(|id| parse_id(id))
+      .collect::<Result<_, _>>()?;
+   Ok(Action::ApplyToTaskIds(task_action, ids))
+}
+
+fn parse_id(raw: &str) -> Result<usize, String> {
+   raw.parse().map_err(|_| format!("Invalid task id `{raw}`"))
+}
+
+fn parse_parallel(raw: &str) -> Result<usize, String> {
+   raw.parse()
+      .map_err(|_| format!("Invalid number of parallel tasks `{raw}`"))
+}
+
+/// Remove a flag from the arguments and return whether it was given.
+fn take_flag(args: &mut Vec<&str>, names: &[&str]) -> bool {
+   let len = args.len();
+   args.retain(|arg| !names.contains(arg));
+   args.len() != len
+}
+
+/// Remove an option and its value from the arguments and return the value.
+fn take_option<'a>(args: &mut Vec<&'a str>, names: &[&str]) -> Result<Option<&'a str>, String> {
+   let Some(index) = args.iter().position(|arg| names.contains(arg)) else {
+      return Ok(None);
+   };
+   if index + 1 >= args.len() {
+      return Err(format!("Missing value for `{}`", args[index]));
+   }
+   let value = args.remove(index + 1);
+   args.remove(index);
+   Ok(Some(value))
+}
+
+/// Parse sort keys like `status asc start desc`. The order defaults to ascending.
+fn parse_sort_keys(args: &[&str]) -> Result<Vec<SortKey>, String> {
+   let mut keys: Vec<SortKey> = Vec::new();
+   for arg in args {
+      match *arg {
+         "asc" | "desc" => {
+            let key = keys
+               .last_mut()
+               .ok_or_else(|| format!("`{arg}` has to follow a column"))?;
+            key.descending = *arg == "desc";
+         }
+         name => keys.push(SortKey {
+            column: HeaderCell::from_name(name).ok_or_else(|| {
+               format!(
+                  "Unknown column `{name}`, expected one of {}",
+                  column_names().join(", ")
+               )
+            })?,
+            descending: false,
+         }),
+      }
+   }
+   Ok(keys)
+}
+
+fn column_names() -> Vec<&'static str> {
+   HeaderCell::ALL.iter().map(HeaderCell::name).collect()
+}
+
+/// A candidate for the word before the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+   pub value: String,
+   pub description: String,
+}
+
+impl Completion {
+   fn new(value: impl Into<String>, description: impl Into<String>) -> Self {
+      Self {
+         value: value.into(),
+         description: description.into(),
+      }
+   }
+}
+
+/// What can be completed besides the command names.
+#[derive(Debug, Default, Clone)]
+pub struct CompletionContext {
+   /// Task ids with their commands.
+   pub tasks: Vec<(usize, String)>,
+   pub groups: Vec<String>,
+}
+
+/// Complete the last word of `input`.
+///
+/// Returns the byte offset where the word starts and the candidates which start with it.
+pub fn complete(input: &str, context: &CompletionContext) -> (usize, Vec<Completion>) {
+   let start = input
+      .char_indices()
+      .rev()
+      .find(|(_, c)| c.is_whitespace())
+      .map_or(0, |(index, c)| index + c.len_utf8());
+   let word = &input[start..];
+   let words: Vec<&str> = input[..start].split_whitespace().collect();
+
+   let groups = || {
+      context
+         .groups
+         .iter()
+         .map(|group| Completion::new(group, "group"))
+         .collect()
+   };
+   let candidates: Vec<Completion> = match words[..] {
+      [] => COMMANDS
+         .iter()
+         .map(|command| Completion::new(command.name, command.description))
+         .collect(),
+      [
+         "start" | "pause" | "kill" | "restart" | "stash" | "enqueue" | "remove",
+         ..,
+      ]
+      | ["edit"] => context
+         .tasks
+         .iter()
+         .filter(|(id, _)| !words.contains(&id.to_string().as_str()))
+         .map(|(id, command)| Completion::new(id.to_string(), command))
+         .collect(),
+      ["group"] => vec![
+         Completion::new("add", "Add a group"),
+         Completion::new("remove", "Remove a group"),
+      ],
+      ["group", "remove"] => groups(),
+      [.., "-g" | "--group"] => groups(),
+      ["sort", ..] => {
+         let mut candidates: Vec<Completion> = HeaderCell::ALL
+            .iter()
+            .map(|column| Completion::new(column.name(), "column"))
+            .collect();
+         if words
+            .last()
+            .is_some_and(|last| HeaderCell::from_name(last).is_some())
+         {
+            candidates.insert(0, Completion::new("asc", "ascending"));
+            candidates.insert(1, Completion::new("desc", "descending"));
+         }
+         candidates
+      }
+      ["filter", ..] => match word.split_once(':') {
+         Some(("status", _)) => StatusFilter::NAMES
+            .iter()
+            .map(|(name, _)| Completion::new(format!("status:{name}"), "status"))
+            .collect(),
+         Some(("group", _)) => context
+            .groups
+            .iter()
+            .map(|group| Completion::new(format!("group:{group}"), "group"))
+            .collect(),
+         Some(_) => Vec::new(),
+         None => KEYS
+            .iter()
+            .map(|key| Completion::new(format!("{key}:"), "filter"))
+            .collect(),
+      },
+      _ => Vec::new(),
+   };
+   let candidates = candidates
+      .into_iter()
+      .filter(|candidate| candidate.value.starts_with(word))
+      .collect();
+   (start, candidates)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_parse_commands() {
+      assert_eq!(
+         parse("kill 3 4"),
+         Ok(Action::ApplyToTaskIds(TaskAction::Kill, vec![3, 4]))
+      );
+      assert_eq!(
+         parse("kill"),
+         Ok(Action::ApplyToSelection(TaskAction::Kill))
+      );
+      assert_eq!(
+         parse("restart -i 2"),
+         Ok(Action::ApplyToTaskIds(TaskAction::Restart, vec![2]))
+      );
+      assert_eq!(
+         parse("group add gpu -p 2"),
+         Ok(Action::AddGroup {
+            name: "gpu".to_string(),
+            parallel: Some(2)
+         })
+      );
+      assert_eq!(
+         parse("parallel 4 -g gpu"),
+         Ok(Action::SetParallel {
+            group: "gpu".to_string(),
+            parallel: 4
+         })
+      );
+      assert_eq!(
+         parse("sort start desc id"),
+         Ok(Action::SortTasks(vec![
+            SortKey {
+               column: HeaderCell::Start,
+               descending: true
+            },
+            SortKey {
+               column: HeaderCell::Id,
+               descending: false
+            },
+         ]))
+      );
+      assert_eq!(
+         parse("filter  status:failed group:gpu "),
+         Ok(Action::SetFilter("status:failed group:gpu".to_string()))
+      );
+
+      assert_eq!(parse("kill x"), Err("Invalid task id `x`".to_string()));
+      assert_eq!(
+         parse("frobnicate"),
+         Err("Unknown command `frobnicate`".to_string())
+      );
+      assert_eq!(
+         parse("parallel -g gpu"),
+         Err("Usage: parallel <n> [-g <group>]".to_string())
+      );
+      assert_eq!(
+         parse("parallel 4 -g"),
+         Err("Missing value for `-g`".to_string())
+      );
+      assert!(parse("sort desc").is_err());
+      assert_eq!(parse("kill '3"), Err("Unbalanced quotes".to_string()));
+      assert_eq!(parse(" "), Err("Empty command".to_string()));
+      // Quotes in a filter are left to the filter parser.
+      assert_eq!(
+         parse("filter command:it's"),
+         Ok(Action::SetFilter("command:it's".to_string()))
+      );
+      assert_eq!(
+         parse("filter label:\"nightly"),
+         Ok(Action::SetFilter("label:\"nightly".to_string()))
+      );
+   }
+
+   #[test]
+   fn test_complete() {
+      let context = CompletionContext {
+         tasks: vec![(3, "sleep 3".to_string()), (12, "ls".to_string())],
+         groups: vec!["default".to_string(), "gpu".to_string()],
+      };
+      let values = |input| {
+         let (start, candidates) = complete(input, &context);
+         let values: Vec<String> = candidates
+            .into_iter()
+            .map(|candidate| candidate.value)
+            .collect();
+         (start, values)
+      };
+
+      assert_eq!(
+         values("re"),
+         (
+            0,
+            vec![
+               "restart".to_string(),
+               "remove".to_string(),
+               "reset".to_string()
+            ]
+         )
+      );
+      assert_eq!(values("kill 3 "), (7, vec!["12".to_string()]));
+      assert_eq!(values("parallel 2 -g g"), (14, vec!["gpu".to_string()]));
+      assert_eq!(
+         values("group "),
+         (6, vec!["add".to_string(), "remove".to_string()])
+      );
+      assert_eq!(
+         values("sort start d"),
+         (
+            11,
+            vec![
+               "desc".to_string(),
+               "dependencies".to_string(),
+               "duration".to_string()
+            ]
+         )
+      );
+      assert_eq!(
+         values("filter status:f"),
+         (7, vec!["status:failed".to_string()])
+      );
+      assert_eq!(values("quit "), (5, vec![]));
+      // Whitespace may take more than one byte, like a non-breaking space.
+      assert_eq!(values("kill\u{a0}1"), (6, vec!["12".to_string()]));
+      assert_eq!(
+         values("kill\u{3000}"),
+         (7, vec!["3".to_string(), "12".to_string()])
+      );
+   }
+}
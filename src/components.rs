@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
    Frame,
@@ -5,9 +7,20 @@ use ratatui::{
 };
 use tokio::sync::mpsc::UnboundedSender;
 
-use crate::{action::Action, tui::Event};
+use crate::{action::Action, config::Config, tui::Event};
 
+pub mod add_task;
+pub mod command_line;
+pub mod confirm;
+pub mod detail;
+pub mod help;
 pub mod home;
+pub mod notifications;
+pub mod overlay;
+pub mod which_key;
+
+/// How often live components, like the duration of a running task, are redrawn.
+pub const LIVE_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 ///
@@ -27,6 +40,55 @@ pub trait Component {
       let _ = tx; // to appease clippy
       Ok(())
    }
+   /// Register a configuration handler that provides configuration settings if necessary.
+   ///
+   /// # Arguments
+   ///
+   /// * `config` - Configuration settings.
+   ///
+   /// # Returns
+   ///
+   /// * [`color_eyre::Result<()>`] - An Ok result or an error.
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      let _ = config; // to appease clippy
+      Ok(())
+   }
+   /// Whether the component is a modal dialog which is currently open.
+   ///
+   /// Key and mouse events only go to the topmost open modal, and modals are drawn above all
+   /// other components.
+   ///
+   /// # Returns
+   ///
+   /// * `bool` - Whether the component is an open modal.
+   fn is_modal(&self) -> bool {
+      false
+   }
+   /// Whether the component changed since it has been drawn.
+   ///
+   /// The screen is only redrawn if a component is dirty. Components set the flag when an event
+   /// or action changes what they show, and reset it in [`Component::draw`].
+   ///
+   /// # Returns
+   ///
+   /// * `bool` - Whether the component has to be drawn again.
+   fn is_dirty(&self) -> bool {
+      false
+   }
+   /// When the component has to be drawn again although nothing happened, because it shows
+   /// something which changes by itself, like an elapsed time or a countdown.
+   ///
+   /// # Arguments
+   ///
+   /// * `last_draw` - When the screen has been drawn last.
+   ///
+   /// # Returns
+   ///
+   /// * `Option<Instant>` - The time of the next redraw, or none if nothing changes by itself.
+   fn next_redraw(&self, last_draw: Instant) -> Option<Instant> {
+      let _ = last_draw; // to appease clippy
+      None
+   }
    /// Initialize the component with a specified area if necessary.
    ///
    /// # Arguments
@@ -0,0 +1,315 @@
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta};
+use crossterm::event::KeyEvent;
+use pueue_lib::message::AddRequest;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::Component;
+use crate::{
+   action::Action,
+   app::Mode,
+   config::{Config, Keymap},
+   filter::{expand_home, parse_duration},
+   keymap::key_hints,
+   widgets::form::{Field, Form, FormState},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddTaskField {
+   Command,
+   Path,
+   Group,
+   Label,
+   Priority,
+   Dependencies,
+   EnqueueAt,
+   Stashed,
+   StartImmediately,
+}
+
+/// Modal form for adding a new task.
+#[derive(Default)]
+pub struct AddTask {
+   command_tx: Option<UnboundedSender<Action>>,
+   /// The open form, or `None` while the form is hidden.
+   form: Option<FormState<AddTaskField>>,
+   error: Option<String>,
+   keymap: Keymap,
+   dirty: bool,
+}
+
+impl AddTask {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   fn open(&mut self, group: Option<&str>, dependencies: &[usize]) {
+      let cwd = std::env::current_dir()
+         .map(|path| path.to_string_lossy().to_string())
+         .unwrap_or_default();
+      let dependencies = dependencies
+         .iter()
+         .map(|id| id.to_string())
+         .collect::<Vec<_>>()
+         .join(", ");
+      self.form = Some(FormState::new(vec![
+         Field::text(AddTaskField::Command, "Command", ""),
+         Field::text(AddTaskField::Path, "Directory", &cwd),
+         Field::text(AddTaskField::Group, "Group", group.unwrap_or("default")),
+         Field::text(AddTaskField::Label, "Label", ""),
+         Field::text(AddTaskField::Priority, "Priority", "").placeholder("0"),
+         Field::text(AddTaskField::Dependencies, "Dependencies", &dependencies)
+            .placeholder("task ids, e.g. 1, 4 (marked tasks are filled in)"),
+         Field::text(AddTaskField::EnqueueAt, "Delay", "")
+            .placeholder("e.g. 30m, 18:00 or 2025-01-01 18:00"),
+         Field::checkbox(AddTaskField::Stashed, "Stashed", false),
+         Field::checkbox(AddTaskField::StartImmediately, "Start now", false),
+      ]));
+      self.error = None;
+   }
+
+   /// Build the request from the form, or describe the first invalid field.
+   fn request(form: &FormState<AddTaskField>) -> Result<AddRequest, String> {
+      let command = form.text(AddTaskField::Command).trim();
+      if command.is_empty() {
+         return Err("Command must not be empty".to_string());
+      }
+
+      let path = match form.text(AddTaskField::Path).trim() {
+         "" => std::env::current_dir().map_err(|e| format!("Invalid directory: {e}"))?,
+         path => expand_home(path),
+      };
+
+      let group = match form.text(AddTaskField::Group).trim() {
+         "" => "default",
+         group => group,
+      };
+
+      let label = Some(form.text(AddTaskField::Label).trim())
+         .filter(|label| !label.is_empty())
+         .map(str::to_string);
+
+      let priority = match form.text(AddTaskField::Priority).trim() {
+         "" => None,
+         priority => Some(
+            priority
+               .parse::<i32>()
+               .map_err(|_| format!("Invalid priority `{priority}`, expected a number"))?,
+         ),
+      };
+
+      let dependencies = form
+         .text(AddTaskField::Dependencies)
+         .split(|c: char| c == ',' || c.is_whitespace())
+         .filter(|id| !id.is_empty())
+         .map(|id| {
+            id.parse::<usize>()
+               .map_err(|_| format!("Invalid dependency `{id}`, expected a task id"))
+         })
+         .collect::<Result<Vec<_>, _>>()?;
+
+      let enqueue_at = match form.text(AddTaskField::EnqueueAt).trim() {
+         "" => None,
+         raw => Some(parse_enqueue_at(raw, Local::now()).ok_or_else(|| {
+            format!("Invalid delay `{raw}`, expected e.g. `30m`, `18:00` or `2025-01-01 18:00`")
+         })?),
+      };
+
+      let stashed = form.checked(AddTaskField::Stashed);
+      let start_immediately = form.checked(AddTaskField::StartImmediately);
+      if start_immediately && (stashed || enqueue_at.is_some()) {
+         return Err("A task can't be started now and be stashed or delayed".to_string());
+      }
+
+      Ok(AddRequest {
+         command: command.to_string(),
+         path,
+         envs: std::env::vars().collect(),
+         start_immediately,
+         stashed: stashed || enqueue_at.is_some(),
+         group: group.to_string(),
+         enqueue_at,
+         dependencies,
+         priority,
+         label,
+      })
+   }
+}
+
+impl Component for AddTask {
+   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+      self.command_tx = Some(tx);
+      Ok(())
+   }
+
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymap = config.keymaps()?.remove(&Mode::AddTask).unwrap_or_default();
+      Ok(())
+   }
+
+   fn is_modal(&self) -> bool {
+      self.form.is_some()
+   }
+
+   fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+      if let Some(form) = &mut self.form
+         && form.handle_key_event(key)
+      {
+         self.error = None;
+         self.dirty = true;
+      }
+      Ok(None)
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::OpenAddTask {
+            group,
+            dependencies,
+         } => {
+            self.open(group.as_deref(), &dependencies);
+            self.dirty = true;
+            return Ok(Some(Action::SwitchMode(Mode::AddTask)));
+         }
+         Action::SubmitAddTask => {
+            if let Some(form) = &self.form {
+               match Self::request(form) {
+                  Ok(request) => {
+                     self.dirty |= self.error.take().is_some();
+                     return Ok(Some(Action::AddTask(Box::new(request))));
+                  }
+                  Err(err) => {
+                     self.error = Some(err);
+                     self.dirty = true;
+                  }
+               }
+            }
+         }
+         // The form stays open with its input until the daemon added the task.
+         Action::AddTaskFailed(err) => {
+            if self.form.is_none() {
+               return Ok(Some(Action::Error(err)));
+            }
+            self.error = Some(err);
+            self.dirty = true;
+         }
+         Action::NextField => {
+            if let Some(form) = &mut self.form {
+               form.focus_next();
+               self.dirty = true;
+            }
+         }
+         Action::PrevField => {
+            if let Some(form) = &mut self.form {
+               form.focus_prev();
+               self.dirty = true;
+            }
+         }
+         Action::SwitchMode(mode) if mode != Mode::AddTask && self.form.is_some() => {
+            self.form = None;
+            self.dirty = true;
+         }
+         _ => {}
+      }
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      let Some(form) = &mut self.form else {
+         return Ok(());
+      };
+
+      let [popup_area] = Layout::vertical([Constraint::Length(Form::height(form) + 1)])
+         .flex(layout::Flex::Center)
+         .areas(area);
+      let [popup_area] = Layout::horizontal([Constraint::Max(80)])
+         .flex(layout::Flex::Center)
+         .areas(popup_area);
+      let [form_area, message_area] =
+         Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(popup_area);
+
+      frame.render_widget(Clear, popup_area);
+      let widget = Form::new("Add task");
+      if let Some(position) = widget.cursor_position(form_area, form) {
+         frame.set_cursor_position(position);
+      }
+      frame.render_stateful_widget(widget, form_area, form);
+
+      let message = match &self.error {
+         Some(err) => Line::from(err.as_str()).red(),
+         None => {
+            let hints = key_hints(
+               &self.keymap,
+               &[
+                  ("Next", &[Action::NextField]),
+                  ("Prev", &[Action::PrevField]),
+               ],
+            );
+            let submit = key_hints(
+               &self.keymap,
+               &[
+                  ("Add", &[Action::SubmitAddTask]),
+                  ("Cancel", &[Action::SwitchMode(Mode::Home)]),
+               ],
+            );
+            // Checkboxes are toggled by the form itself, so Space isn't in the keymap.
+            Line::from(format!("{hints}  Toggle : space  {submit}")).dark_gray()
+         }
+      };
+      frame.render_widget(message, message_area);
+      Ok(())
+   }
+}
+
+/// Parse when a delayed task should be enqueued: a duration from `now` like `30m`, a time of
+/// day like `18:00` (tomorrow if it has passed already) or a date and time.
+fn parse_enqueue_at(raw: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+   if let Some(duration) = parse_duration(raw) {
+      return Some(now + duration);
+   }
+   for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+      if let Ok(datetime) = NaiveDateTime::parse_from_str(raw, format) {
+         return datetime.and_local_timezone(Local).earliest();
+      }
+   }
+   for format in ["%H:%M:%S", "%H:%M"] {
+      if let Ok(time) = NaiveTime::parse_from_str(raw, format) {
+         let today = now
+            .date_naive()
+            .and_time(time)
+            .and_local_timezone(Local)
+            .earliest()?;
+         return Some(if today > now {
+            today
+         } else {
+            today + TimeDelta::days(1)
+         });
+      }
+   }
+   None
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_parse_enqueue_at() {
+      let at = |raw| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").unwrap();
+      let now = at("2025-03-10 12:00").and_local_timezone(Local).unwrap();
+      let parse = |raw| parse_enqueue_at(raw, now).map(|dt| dt.naive_local());
+
+      assert_eq!(parse("90m"), Some(at("2025-03-10 13:30")));
+      assert_eq!(parse("18:00"), Some(at("2025-03-10 18:00")));
+      assert_eq!(parse("08:00"), Some(at("2025-03-11 08:00")));
+      assert_eq!(parse("2025-04-01 09:15"), Some(at("2025-04-01 09:15")));
+      assert_eq!(parse("tomorrow"), None);
+   }
+}
@@ -0,0 +1,297 @@
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::Component;
+use crate::{
+   action::Action,
+   app::Mode,
+   command::{self, COMMANDS, Completion, CompletionContext},
+   fuzzy::fuzzy_match,
+   widgets::text_input::{TextInput, TextInputState},
+};
+
+/// The most completions shown at once.
+const MAX_COMPLETIONS: usize = 10;
+
+/// The `:` command line, which also serves as the command palette.
+///
+/// Commands are parsed into actions, so they run through the same dispatch as key bindings.
+#[derive(Default)]
+pub struct CommandLine {
+   command_tx: Option<UnboundedSender<Action>>,
+   open: bool,
+   /// Whether all commands are listed and fuzzy matched while typing.
+   palette: bool,
+   input: TextInputState,
+   completions: Vec<Completion>,
+   selected: Option<usize>,
+   context: CompletionContext,
+   error: Option<String>,
+   dirty: bool,
+}
+
+impl CommandLine {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   fn open(&mut self, palette: bool) -> Option<Action> {
+      self.open = true;
+      self.dirty = true;
+      self.palette = palette;
+      self.input.clear();
+      self.error = None;
+      self.update_completions();
+      Some(Action::SwitchMode(Mode::Command))
+   }
+
+   fn close(&mut self) {
+      self.dirty |= self.open;
+      self.open = false;
+      self.completions.clear();
+      self.selected = None;
+   }
+
+   /// Whether the first word, the command name, is still being typed.
+   fn is_typing_command(&self) -> bool {
+      !self
+         .input
+         .value()
+         .trim_start()
+         .contains(char::is_whitespace)
+   }
+
+   /// Refresh the shown completions after the input changed.
+   ///
+   /// The command line only shows them once Tab has been pressed, the palette always lists the
+   /// commands which fuzzy match the input.
+   fn update_completions(&mut self) {
+      self.selected = None;
+      if self.palette && self.is_typing_command() {
+         let query = self.input.value().trim();
+         let mut matches: Vec<(i64, &command::Command)> = COMMANDS
+            .iter()
+            .filter_map(|command| Some((fuzzy_match(query, command.name)?.score, command)))
+            .collect();
+         matches.sort_by_key(|(score, _)| -score);
+         self.completions = matches
+            .into_iter()
+            .map(|(_, command)| Completion {
+               value: command.name.to_string(),
+               description: command.description.to_string(),
+            })
+            .collect();
+         self.selected = (!self.completions.is_empty()).then_some(0);
+      } else if !self.completions.is_empty() {
+         self.completions = command::complete(self.input.value(), &self.context).1;
+      }
+   }
+
+   /// Complete the last word with the selected candidate, or with the common prefix of all.
+   fn complete(&mut self) {
+      self.dirty = true;
+      let (start, candidates) = command::complete(self.input.value(), &self.context);
+      let value = match (self.selected, &candidates[..]) {
+         (Some(selected), _) => self.completions.get(selected).map(|c| c.value.clone()),
+         (None, [candidate]) => Some(candidate.value.clone()),
+         _ => None,
+      };
+      let input = self.input.value()[..start].to_string();
+      match value {
+         Some(value) => {
+            // Filter keys like `status:` are continued by their value.
+            let separator = if value.ends_with(':') { "" } else { " " };
+            self.input = TextInputState::new(&format!("{input}{value}{separator}"));
+            self.completions.clear();
+            self.update_completions();
+         }
+         None => {
+            let prefix = common_prefix(candidates.iter().map(|c| c.value.as_str()));
+            if prefix.len() > self.input.value().len() - start {
+               self.input = TextInputState::new(&format!("{input}{prefix}"));
+            }
+            self.completions = candidates;
+            self.selected = None;
+         }
+      }
+   }
+
+   fn select_completion(&mut self, delta: isize) {
+      let len = self.completions.len();
+      if len == 0 {
+         return;
+      }
+      self.dirty = true;
+      self.selected = Some(match self.selected {
+         Some(selected) => (selected as isize + delta).rem_euclid(len as isize) as usize,
+         None if delta < 0 => len - 1,
+         None => 0,
+      });
+   }
+
+   fn submit(&mut self) -> color_eyre::Result<Option<Action>> {
+      self.dirty = true;
+      // In the palette, Enter picks the selected command first.
+      if self.palette
+         && self.is_typing_command()
+         && let Some(name) = self
+            .selected
+            .and_then(|selected| self.completions.get(selected))
+            .map(|completion| completion.value.clone())
+      {
+         let command = COMMANDS.iter().find(|command| command.name == name);
+         if !command.is_some_and(|command| command.takes_no_args()) {
+            self.input = TextInputState::new(&format!("{name} "));
+            self.update_completions();
+            return Ok(None);
+         }
+         self.input = TextInputState::new(&name);
+      }
+
+      match command::parse(self.input.value()) {
+         Ok(action) => {
+            self.close();
+            if let Some(tx) = &self.command_tx {
+               tx.send(Action::SwitchMode(Mode::Home))?;
+            }
+            Ok(Some(action))
+         }
+         Err(err) => {
+            self.error = Some(err);
+            Ok(None)
+         }
+      }
+   }
+}
+
+impl Component for CommandLine {
+   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+      self.command_tx = Some(tx);
+      Ok(())
+   }
+
+   fn is_modal(&self) -> bool {
+      self.open
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+      if self.open && self.input.handle_key_event(key) {
+         self.error = None;
+         self.dirty = true;
+         self.update_completions();
+      }
+      Ok(None)
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::StateChanged(diff) => {
+            let tasks = &mut self.context.tasks;
+            tasks.retain(|(id, _)| {
+               !diff.removed.contains(id) && diff.updated().all(|task| task.id != *id)
+            });
+            tasks.extend(diff.updated().map(|task| (task.id, task.command.clone())));
+            tasks.sort_by_key(|(id, _)| *id);
+            if let Some(groups) = &diff.groups {
+               self.context.groups = groups.keys().cloned().collect();
+            }
+         }
+         Action::OpenCommandLine => return Ok(self.open(false)),
+         Action::OpenPalette => return Ok(self.open(true)),
+         Action::SwitchMode(mode) if mode != Mode::Command => self.close(),
+         _ if !self.open => {}
+         Action::CompleteCommand => self.complete(),
+         Action::NextCompletion => self.select_completion(1),
+         Action::PrevCompletion => self.select_completion(-1),
+         Action::SubmitCommand => return self.submit(),
+         _ => {}
+      }
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      if !self.open {
+         return Ok(());
+      }
+
+      let items: Vec<ListItem> = self
+         .completions
+         .iter()
+         .map(|completion| {
+            ListItem::new(Line::from(vec![
+               Span::from(completion.value.clone()).bold(),
+               Span::from("  "),
+               Span::from(completion.description.clone()).dark_gray(),
+            ]))
+         })
+         .collect();
+      let list_height = items.len().min(MAX_COMPLETIONS) as u16;
+      let mut list_state = ListState::default().with_selected(self.selected);
+      let list = List::new(items).highlight_style(Style::new().reversed());
+
+      let input = TextInput::new(":");
+      if self.palette {
+         let [popup_area] = Layout::vertical([Constraint::Length(list_height + 4)])
+            .flex(layout::Flex::Start)
+            .areas(area.inner(Margin::new(0, 2)));
+         let [popup_area] = Layout::horizontal([Constraint::Max(72)])
+            .flex(layout::Flex::Center)
+            .areas(popup_area);
+         let block = Block::bordered().title(" Commands ");
+         let [input_area, error_area, list_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+         ])
+         .areas(block.inner(popup_area));
+
+         frame.render_widget(Clear, popup_area);
+         frame.render_widget(block, popup_area);
+         frame.set_cursor_position(input.cursor_position(input_area, &self.input));
+         frame.render_stateful_widget(input, input_area, &mut self.input);
+         if let Some(err) = &self.error {
+            frame.render_widget(Line::from(err.as_str()).red(), error_area);
+         }
+         frame.render_stateful_widget(list, list_area, &mut list_state);
+      } else {
+         // Like vim, the command line replaces the status bar.
+         let [_, list_area, input_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(list_height),
+            Constraint::Length(1),
+         ])
+         .areas(area);
+
+         frame.render_widget(Clear, list_area);
+         frame.render_stateful_widget(list, list_area, &mut list_state);
+         frame.render_widget(Clear, input_area);
+         if let Some(err) = &self.error {
+            frame.render_widget(Line::from(err.as_str()).red().right_aligned(), input_area);
+         }
+         frame.set_cursor_position(input.cursor_position(input_area, &self.input));
+         frame.render_stateful_widget(input, input_area, &mut self.input);
+      }
+      Ok(())
+   }
+}
+
+fn common_prefix<'a>(mut values: impl Iterator<Item = &'a str>) -> &'a str {
+   let Some(first) = values.next() else {
+      return "";
+   };
+   values.fold(first, |prefix, value| {
+      let len = prefix
+         .char_indices()
+         .zip(value.chars())
+         .take_while(|((_, a), b)| a == b)
+         .last()
+         .map_or(0, |((i, a), _)| i + a.len_utf8());
+      &prefix[..len]
+   })
+}
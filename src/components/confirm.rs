@@ -0,0 +1,179 @@
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::Component;
+use crate::{
+   action::{Action, Confirmation},
+   app::Mode,
+};
+
+/// Modal yes/no dialog shown before actions which can't be undone.
+///
+/// "No" has the focus when the dialog opens, so pressing Enter right away does nothing.
+#[derive(Default)]
+pub struct Confirm {
+   command_tx: Option<UnboundedSender<Action>>,
+   confirmation: Option<Confirmation>,
+   /// Whether "Yes" has the focus.
+   yes: bool,
+   /// The mode to go back to once the dialog is closed.
+   return_mode: Mode,
+   mode: Mode,
+   dirty: bool,
+}
+
+impl Confirm {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   fn close(&mut self, accept: bool) -> color_eyre::Result<Option<Action>> {
+      let Some(confirmation) = self.confirmation.take() else {
+         return Ok(None);
+      };
+      self.dirty = true;
+      if let Some(tx) = &self.command_tx {
+         tx.send(Action::SwitchMode(self.return_mode))?;
+      }
+      Ok(accept.then_some(confirmation.action))
+   }
+}
+
+impl Component for Confirm {
+   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+      self.command_tx = Some(tx);
+      Ok(())
+   }
+
+   fn is_modal(&self) -> bool {
+      self.confirmation.is_some()
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::SwitchMode(mode) => self.mode = mode,
+         Action::RequestConfirmation(confirmation) => {
+            self.confirmation = Some(*confirmation);
+            self.yes = false;
+            self.return_mode = self.mode;
+            self.dirty = true;
+            return Ok(Some(Action::SwitchMode(Mode::Confirm)));
+         }
+         Action::ConfirmToggle => {
+            self.yes = !self.yes;
+            self.dirty = true;
+         }
+         Action::ConfirmAccept => return self.close(true),
+         Action::ConfirmReject => return self.close(false),
+         Action::ConfirmSubmit => return self.close(self.yes),
+         _ => {}
+      }
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      let Some(confirmation) = &self.confirmation else {
+         return Ok(());
+      };
+
+      let width = area.width.min(64);
+      let text_width = width.saturating_sub(4).max(1) as usize;
+      let text_height: usize = confirmation
+         .message
+         .lines()
+         .map(|line| line.chars().count().div_ceil(text_width).max(1))
+         .sum();
+      let [popup_area] = Layout::vertical([Constraint::Length(text_height as u16 + 4)])
+         .flex(layout::Flex::Center)
+         .areas(area);
+      let [popup_area] = Layout::horizontal([Constraint::Length(width)])
+         .flex(layout::Flex::Center)
+         .areas(popup_area);
+
+      let block = Block::bordered()
+         .title(format!(" {} ", confirmation.title))
+         .title_bottom(Line::from(" y/n ").right_aligned())
+         .padding(Padding::horizontal(1))
+         .red();
+      let [message_area, buttons_area] =
+         Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(block.inner(popup_area));
+
+      let button = |label: &'static str, focused: bool| {
+         if focused {
+            Span::from(label).bold().reversed()
+         } else {
+            Span::from(label)
+         }
+      };
+      let buttons = Line::from(vec![
+         button(" No ", !self.yes),
+         Span::from("   "),
+         button(" Yes ", self.yes),
+      ])
+      .centered();
+
+      frame.render_widget(Clear, popup_area);
+      frame.render_widget(block, popup_area);
+      frame.render_widget(
+         Paragraph::new(confirmation.message.as_str())
+            .wrap(Wrap { trim: false })
+            .reset(),
+         message_area,
+      );
+      frame.render_widget(buttons.reset(), buttons_area);
+      Ok(())
+   }
+}
+
+/// Describe tasks by their ids, collapsing consecutive ids into ranges.
+pub fn task_list(ids: &[usize]) -> String {
+   let mut ids = ids.to_vec();
+   ids.sort_unstable();
+   ids.dedup();
+
+   let mut ranges: Vec<(usize, usize)> = Vec::new();
+   for id in ids.iter().copied() {
+      match ranges.last_mut() {
+         Some((_, end)) if *end + 1 == id => *end = id,
+         _ => ranges.push((id, id)),
+      }
+   }
+   let ranges = ranges
+      .iter()
+      .map(|&(start, end)| match end - start {
+         0 => start.to_string(),
+         1 => format!("{start}, {end}"),
+         _ => format!("{start}-{end}"),
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+
+   match ids.len() {
+      0 => "no tasks".to_string(),
+      1 => format!("task {ranges}"),
+      n => format!("{n} tasks ({ranges})"),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_task_list() {
+      assert_eq!(task_list(&[]), "no tasks");
+      assert_eq!(task_list(&[4]), "task 4");
+      assert_eq!(
+         task_list(&[9, 1, 2, 3, 5, 6, 11]),
+         "7 tasks (1-3, 5, 6, 9, 11)"
+      );
+   }
+}
@@ -0,0 +1,391 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+use pueue_lib::{Task, TaskStatus};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, LIVE_REDRAW_INTERVAL};
+use crate::{
+   action::Action,
+   app::Mode,
+   client::TaskLog,
+   config::{Config, Keymap},
+   keymap::key_hints,
+   widgets::{
+      status_bar::StatusBar,
+      task_table::{TIME_FORMAT, TaskTable, duration, format_duration},
+   },
+};
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum View {
+   #[default]
+   Output,
+   Environment,
+}
+
+/// Full-screen view of a single task's metadata and output.
+#[derive(Default)]
+pub struct Detail {
+   command_tx: Option<UnboundedSender<Action>>,
+   task_id: Option<usize>,
+   task: Option<Task>,
+   output: Vec<String>,
+   /// Raw text after the last newline, which may still be continued by the next chunk.
+   partial_line: String,
+   output_complete: bool,
+   /// Whether the output view sticks to the bottom as new output arrives.
+   follow: bool,
+   view: View,
+   scroll: usize,
+   page_height: usize,
+   mode: Mode,
+   keymap: Keymap,
+   daemon_version: Option<(String, bool)>,
+   dirty: bool,
+}
+
+impl Detail {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   fn open(&mut self, task_id: usize) {
+      *self = Self {
+         command_tx: self.command_tx.take(),
+         task_id: Some(task_id),
+         mode: self.mode,
+         keymap: std::mem::take(&mut self.keymap),
+         daemon_version: self.daemon_version.take(),
+         output_complete: true,
+         ..Self::default()
+      };
+   }
+
+   fn close(&mut self) {
+      self.dirty |= self.task_id.is_some();
+      self.task_id = None;
+      self.task = None;
+      self.output.clear();
+      self.partial_line.clear();
+   }
+
+   fn set_output(&mut self, output: &str) {
+      self.output.clear();
+      self.partial_line.clear();
+      self.append_output(output);
+   }
+
+   fn append_output(&mut self, chunk: &str) {
+      let mut text = std::mem::take(&mut self.partial_line);
+      if !text.is_empty() {
+         self.output.pop();
+      }
+      text.push_str(chunk);
+
+      let complete_len = text.rfind('\n').map_or(0, |i| i + 1);
+      self.partial_line = text[complete_len..].to_string();
+      self.output.extend(sanitize_output(&text));
+   }
+
+   fn content_len(&self) -> usize {
+      match self.view {
+         View::Output => self.output.len(),
+         View::Environment => self.task.as_ref().map_or(0, |t| t.envs.len()),
+      }
+   }
+
+   /// Start following the output when the task starts running, unless the user scrolled up.
+   fn set_task(&mut self, task: Task) {
+      let was_running = self.task.as_ref().is_some_and(|task| task.is_running());
+      if task.is_running() && !was_running && self.scroll >= self.max_scroll() {
+         self.follow = true;
+      }
+      self.task = Some(task);
+   }
+
+   fn max_scroll(&self) -> usize {
+      self.content_len().saturating_sub(self.page_height)
+   }
+
+   fn scroll_by(&mut self, delta: isize) {
+      self.scroll = self
+         .scroll
+         .saturating_add_signed(delta)
+         .min(self.max_scroll());
+   }
+
+   fn metadata_lines(task: &Task) -> Vec<Line<'static>> {
+      let (start, end) = task.start_and_end();
+      let enqueued_at = match &task.status {
+         TaskStatus::Queued { enqueued_at }
+         | TaskStatus::Running { enqueued_at, .. }
+         | TaskStatus::Paused { enqueued_at, .. }
+         | TaskStatus::Done { enqueued_at, .. } => Some(*enqueued_at),
+         _ => None,
+      };
+      let enqueue_at = match &task.status {
+         TaskStatus::Stashed { enqueue_at } => *enqueue_at,
+         _ => None,
+      };
+      let result = match &task.status {
+         TaskStatus::Done { result, .. } => TaskTable::task_result_to_string(result),
+         _ => String::new(),
+      };
+
+      [
+         ("Id", task.id.to_string()),
+         ("Status", TaskTable::task_status_to_string(&task.status)),
+         ("Result", result),
+         ("Command", task.command.clone()),
+         ("Path", task.path.to_string_lossy().to_string()),
+         ("Group", task.group.clone()),
+         ("Label", task.label.clone().unwrap_or_default()),
+         ("Priority", task.priority.to_string()),
+         (
+            "Dependencies",
+            task
+               .dependencies
+               .iter()
+               .map(|id| id.to_string())
+               .collect::<Vec<_>>()
+               .join(", "),
+         ),
+         ("Created", format_time(Some(task.created_at))),
+         ("Enqueue At", format_time(enqueue_at)),
+         ("Enqueued", format_time(enqueued_at)),
+         ("Start", format_time(start)),
+         ("End", format_time(end)),
+         (
+            "Duration",
+            duration(task, Local::now()).map_or(String::new(), format_duration),
+         ),
+      ]
+      .into_iter()
+      .filter(|(_, value)| !value.is_empty())
+      .map(|(key, value)| {
+         Line::from(vec![
+            Span::from(format!("{key:<13}")).bold(),
+            Span::from(value),
+         ])
+      })
+      .collect()
+   }
+}
+
+impl Component for Detail {
+   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+      self.command_tx = Some(tx);
+      Ok(())
+   }
+
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymap = config.keymaps()?.remove(&Mode::Detail).unwrap_or_default();
+      Ok(())
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   /// The duration of a running task grows.
+   fn next_redraw(&self, last_draw: Instant) -> Option<Instant> {
+      self
+         .task
+         .as_ref()
+         .is_some_and(|task| task.is_running())
+         .then(|| last_draw + LIVE_REDRAW_INTERVAL)
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::OpenDetail(task_id) => self.open(task_id),
+         Action::DaemonVersion {
+            version,
+            compatible,
+         } => self.daemon_version = Some((version, compatible)),
+         Action::SwitchMode(mode) => {
+            self.mode = mode;
+            if mode != Mode::Detail && !mode.is_overlay() {
+               self.close();
+            }
+            return Ok(None);
+         }
+         _ if self.task_id.is_none() => return Ok(None),
+         Action::UpdateLog(log) if Some(log.task.id) == self.task_id => {
+            let TaskLog {
+               task,
+               output,
+               output_complete,
+            } = *log;
+            self.set_task(task);
+            self.set_output(&output);
+            self.output_complete = output_complete;
+            self.scroll = self.scroll.min(self.max_scroll());
+         }
+         Action::AppendLog(task_id, chunk) if Some(task_id) == self.task_id => {
+            self.append_output(&chunk);
+         }
+         Action::StateChanged(diff) => {
+            let Some(task) = diff.updated().find(|task| Some(task.id) == self.task_id) else {
+               return Ok(None);
+            };
+            self.set_task(task.clone());
+         }
+         // The help is open above the view and takes the navigation.
+         _ if self.mode != Mode::Detail => return Ok(None),
+         Action::ToggleDetailView => {
+            self.view = match self.view {
+               View::Output => View::Environment,
+               View::Environment => View::Output,
+            };
+            self.scroll = 0;
+         }
+         Action::ScrollUp => {
+            self.follow = false;
+            self.scroll_by(-1);
+         }
+         Action::ScrollDown => self.scroll_by(1),
+         Action::ScrollPageUp => {
+            self.follow = false;
+            self.scroll_by(-(self.page_height as isize));
+         }
+         Action::ScrollPageDown => self.scroll_by(self.page_height as isize),
+         Action::ScrollTop => {
+            self.follow = false;
+            self.scroll = 0;
+         }
+         Action::ScrollBottom => {
+            self.follow = true;
+            self.scroll = self.max_scroll();
+         }
+         _ => return Ok(None),
+      }
+      self.dirty = true;
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      let Some(task_id) = self.task_id else {
+         return Ok(());
+      };
+      frame.render_widget(Clear, area);
+
+      let metadata = self
+         .task
+         .as_ref()
+         .map_or_else(Vec::new, Self::metadata_lines);
+      let [metadata_area, content_area, status_bar_area] = Layout::vertical([
+         Constraint::Length(metadata.len() as u16 + 2),
+         Constraint::Fill(1),
+         Constraint::Length(1),
+      ])
+      .areas(area);
+
+      frame.render_widget(
+         Paragraph::new(metadata).block(Block::bordered().title(format!(" Task {task_id} "))),
+         metadata_area,
+      );
+
+      let is_running = self.task.as_ref().is_some_and(Task::is_running);
+      let block = match self.view {
+         View::Output if is_running && self.follow => {
+            Block::bordered().title(" Output (following) ")
+         }
+         View::Output if is_running => Block::bordered().title(" Output (paused, End to follow) "),
+         View::Output if self.output_complete => Block::bordered().title(" Output "),
+         View::Output => Block::bordered().title(" Output (truncated) "),
+         View::Environment => Block::bordered().title(" Environment "),
+      };
+      self.page_height = block.inner(content_area).height as usize;
+      self.scroll = if self.follow && self.view == View::Output {
+         self.max_scroll()
+      } else {
+         self.scroll.min(self.max_scroll())
+      };
+
+      let lines: Vec<Line> = match self.view {
+         View::Output => self
+            .output
+            .iter()
+            .skip(self.scroll)
+            .take(self.page_height)
+            .map(|line| Line::from(line.as_str()))
+            .collect(),
+         View::Environment => {
+            let mut envs: Vec<_> = self
+               .task
+               .as_ref()
+               .map_or_else(Vec::new, |t| t.envs.iter().collect());
+            envs.sort();
+            envs
+               .into_iter()
+               .skip(self.scroll)
+               .take(self.page_height)
+               .map(|(key, value)| {
+                  Line::from(vec![
+                     Span::from(key.as_str()).bold(),
+                     Span::from(format!("={value}")),
+                  ])
+               })
+               .collect()
+         }
+      };
+      frame.render_widget(Paragraph::new(lines).block(block), content_area);
+
+      let status_bar = StatusBar::new(&key_hints(
+         &self.keymap,
+         &[
+            ("Back", &[Action::SwitchMode(Mode::Home)]),
+            ("Scroll", &[Action::ScrollDown, Action::ScrollUp]),
+            ("Output/Env", &[Action::ToggleDetailView]),
+            ("Help", &[Action::OpenHelp]),
+         ],
+      ))
+      .daemon_version(self.daemon_version.as_ref());
+      frame.render_widget(status_bar, status_bar_area);
+      Ok(())
+   }
+}
+
+fn format_time(time: Option<DateTime<Local>>) -> String {
+   time.map_or(String::new(), |t| t.format(TIME_FORMAT).to_string())
+}
+
+/// Split task output into lines that can be rendered safely.
+///
+/// Escape sequences are dropped and carriage returns only keep the text after them,
+/// which is what a terminal would end up showing for progress bars and the like.
+fn sanitize_output(output: &str) -> Vec<String> {
+   output
+      .lines()
+      .map(|line| {
+         let line = line
+            .rsplit('\r')
+            .find(|s| !s.is_empty())
+            .unwrap_or_default();
+         let mut sanitized = String::with_capacity(line.len());
+         let mut chars = line.chars();
+         while let Some(c) = chars.next() {
+            match c {
+               '\x1b' => {
+                  // Skip CSI sequences like `\x1b[31m` up to and including the final byte.
+                  if chars.next() == Some('[') {
+                     for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                           break;
+                        }
+                     }
+                  }
+               }
+               '\t' => sanitized.push_str("    "),
+               c if c.is_control() => {}
+               c => sanitized.push(c),
+            }
+         }
+         sanitized
+      })
+      .collect()
+}
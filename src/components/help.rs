@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, overlay::Overlay};
+use crate::{
+   action::{Action, Category},
+   app::Mode,
+   config::{Config, Keymap},
+   keymap::{bound_keys, key_hints},
+};
+
+/// Scrollable list of the key bindings of the mode it was opened from.
+pub struct Help {
+   keymaps: HashMap<Mode, Keymap>,
+   /// Its return mode is the mode whose bindings are shown.
+   overlay: Overlay,
+}
+
+impl Help {
+   pub fn new() -> Self {
+      Self {
+         keymaps: HashMap::new(),
+         overlay: Overlay::new(Mode::Help, Action::OpenHelp, Action::CloseHelp),
+      }
+   }
+
+   /// The bindings of the mode grouped by category, one line per action.
+   fn lines(&self) -> Vec<Line<'static>> {
+      let Some(keymap) = self.keymaps.get(&self.overlay.return_mode()) else {
+         return Vec::new();
+      };
+      let mut categories: BTreeMap<Category, Vec<(String, String)>> = BTreeMap::new();
+      for action in keymap.values() {
+         let description = action.description();
+         let bindings = categories.entry(action.category()).or_default();
+         if bindings.iter().all(|(_, other)| *other != description) {
+            bindings.push((bound_keys(keymap, action).join(", "), description));
+         }
+      }
+
+      let key_width = categories
+         .values()
+         .flatten()
+         .map(|(keys, _)| keys.chars().count())
+         .max()
+         .unwrap_or_default();
+      let mut lines = Vec::new();
+      for (category, mut bindings) in categories {
+         bindings.sort_by(|(_, a), (_, b)| a.cmp(b));
+         if !lines.is_empty() {
+            lines.push(Line::default());
+         }
+         lines.push(Line::from(category.as_str().to_string()).bold());
+         lines.extend(bindings.into_iter().map(|(keys, description)| {
+            Line::from(vec![
+               Span::from(format!("  {keys:<key_width$}  ")).yellow(),
+               Span::from(description),
+            ])
+         }));
+      }
+      lines
+   }
+}
+
+impl Component for Help {
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymaps = config.keymaps()?;
+      Ok(())
+   }
+
+   fn is_modal(&self) -> bool {
+      self.overlay.is_open()
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.overlay.is_dirty()
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      Ok(self.overlay.update(&action))
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      if !self.overlay.is_open() {
+         return Ok(());
+      }
+
+      let lines = self.lines();
+      let [popup_area] = Layout::vertical([Constraint::Max(lines.len() as u16 + 2)])
+         .flex(layout::Flex::Center)
+         .areas(area);
+      let [popup_area] = Layout::horizontal([Constraint::Max(64)])
+         .flex(layout::Flex::Center)
+         .areas(popup_area);
+
+      let hints = self
+         .keymaps
+         .get(&Mode::Help)
+         .map(|keymap| {
+            key_hints(
+               keymap,
+               &[
+                  ("Scroll", &[Action::ScrollDown, Action::ScrollUp]),
+                  ("Close", &[Action::CloseHelp]),
+               ],
+            )
+         })
+         .unwrap_or_default();
+      let block = Block::bordered()
+         .title(format!(" Help: {:?} ", self.overlay.return_mode()))
+         .title_bottom(Line::from(format!(" {hints} ")).right_aligned())
+         .padding(Padding::horizontal(1));
+      self.overlay.render(frame, popup_area, block, lines);
+      Ok(())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_lines() {
+      let mut help = Help::new();
+      help.register_config_handler(Config::default()).unwrap();
+      help.update(Action::SwitchMode(Mode::Detail)).unwrap();
+      help.update(Action::OpenHelp).unwrap();
+
+      let text: Vec<String> = help
+         .lines()
+         .iter()
+         .map(|line| line.to_string().trim_end().to_string())
+         .collect();
+      assert_eq!(
+         &text[..3],
+         ["General", "  q, esc    Back", "  ?         Help"]
+      );
+      assert!(text.contains(&"  k, up     Scroll up".to_string()));
+   }
+}
@@ -1,16 +1,26 @@
-use std::vec;
+use std::{cmp::Ordering, collections::HashMap, time::Instant, vec};
 
-use crossterm::event::{KeyEvent, KeyModifiers};
-use pueue_lib::Task;
-use ratatui::{prelude::*, widgets::*};
+use chrono::Local;
+use crossterm::event::KeyEvent;
+use pueue_lib::{State, Task, TaskStatus};
+use ratatui::prelude::*;
 use tokio::sync::mpsc::UnboundedSender;
 
-use super::Component;
+use super::{Component, LIVE_REDRAW_INTERVAL};
 use crate::{
-   action::Action,
+   action::{Action, Confirmation, TaskAction},
+   app::Mode,
+   client::single_line,
+   components::confirm::task_list,
+   config::{Config, Keymap},
+   filter::{Filter, FilterError},
+   fuzzy::fuzzy_match,
+   keymap::{describe_pending, key_hints},
    widgets::{
+      group_tabs::GroupTabs,
       status_bar::StatusBar,
-      task_table::{TaskTable, TaskTableState},
+      task_table::{CellHighlights, HeaderCell, SortKey, TaskTable, TaskTableState},
+      text_input::{TextInput, TextInputState},
    },
 };
 
@@ -18,103 +28,706 @@ use crate::{
 pub struct Home {
    command_tx: Option<UnboundedSender<Action>>,
    table_state: TaskTableState,
+   state: State,
+   /// The group whose tasks are shown, or `None` to show the tasks of all groups.
+   selected_group: Option<String>,
    tasks: Vec<Task>,
+   /// Changes whenever `tasks` or `highlights` change, so the table knows when to rebuild rows.
+   generation: u64,
+   search: TextInputState,
+   highlights: HashMap<usize, CellHighlights>,
+   filter_input: TextInputState,
+   /// The applied filter together with the expression it was parsed from.
+   filter: Option<(String, Filter)>,
+   filter_error: Option<FilterError>,
+   /// A task to select as soon as it shows up, e.g. one that has just been added.
+   pending_selection: Option<usize>,
+   config: Config,
+   mode: Mode,
+   pending_keys: String,
+   keymap: Keymap,
+   /// The columns to sort by when not searching, search results are sorted by their score.
+   sort: Vec<SortKey>,
+   /// Why the daemon can't be reached and when the next attempt to reconnect is made.
+   disconnected: Option<(String, Instant)>,
+   /// The protocol version of the daemon and whether it is compatible with this build.
+   daemon_version: Option<(String, bool)>,
+   dirty: bool,
 }
 
 impl Home {
    pub fn new() -> Self {
       Self {
          command_tx: None,
-         table_state: (TableState::new().with_selected(0), ScrollbarState::new(0)),
+         table_state: TaskTableState::new(),
+         state: State::new(),
+         selected_group: None,
          tasks: vec![],
+         generation: 0,
+         search: TextInputState::default(),
+         highlights: HashMap::new(),
+         filter_input: TextInputState::default(),
+         filter: None,
+         filter_error: None,
+         pending_selection: None,
+         config: Config::default(),
+         mode: Mode::Home,
+         pending_keys: String::new(),
+         keymap: Keymap::new(),
+         sort: Vec::new(),
+         disconnected: None,
+         daemon_version: None,
+         dirty: true,
       }
    }
 }
 
+impl Home {
+   /// Run an action on the tasks, after asking for confirmation if it can't be undone.
+   fn apply_to_tasks(&self, task_action: TaskAction, tasks: Vec<Task>) -> Option<Action> {
+      if tasks.is_empty() {
+         return None;
+      }
+      let ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+      let action = Action::ApplyToTasks(task_action, tasks);
+      let confirm = &self.config.confirm;
+      Some(match task_action {
+         TaskAction::Kill => Self::confirm(
+            confirm.kill,
+            "Kill",
+            format!("Kill {}?", task_list(&ids)),
+            action,
+         ),
+         TaskAction::Remove => Self::confirm(
+            confirm.remove,
+            "Remove",
+            format!(
+               "Remove {}? Their logs are deleted as well.",
+               task_list(&ids)
+            ),
+            action,
+         ),
+         _ => action,
+      })
+   }
+
+   /// Filter the tasks with the content of the filter input.
+   fn apply_filter(&mut self) -> Option<Action> {
+      match Filter::parse(self.filter_input.value()) {
+         Ok(filter) => {
+            self.filter =
+               (!filter.is_empty()).then(|| (self.filter_input.value().trim().to_string(), filter));
+            self.filter_error = None;
+            self.update_tasks();
+            self.select_row(0);
+            Some(Action::SwitchMode(Mode::Home))
+         }
+         Err(err) => {
+            self.filter_error = Some(err);
+            None
+         }
+      }
+   }
+
+   /// Hints for the status bar, with the actions for marked tasks while there are any.
+   fn key_hints(&self) -> String {
+      const START_PAUSE_KILL: &[Action] = &[
+         Action::ApplyToSelection(TaskAction::Start),
+         Action::ApplyToSelection(TaskAction::Pause),
+         Action::ApplyToSelection(TaskAction::Kill),
+      ];
+      const RESTART: &[Action] = &[
+         Action::ApplyToSelection(TaskAction::Restart),
+         Action::ApplyToSelection(TaskAction::RestartAsNew),
+      ];
+
+      let marked = self.table_state.marked_ids(&self.tasks).len();
+      if marked == 0 {
+         return key_hints(
+            &self.keymap,
+            &[
+               ("Quit", &[Action::Quit]),
+               ("Help", &[Action::OpenHelp]),
+               ("Command", &[Action::OpenCommandLine]),
+               ("Group", &[Action::NextGroup, Action::PrevGroup]),
+               ("Search", &[Action::SwitchMode(Mode::Search)]),
+               ("Filter", &[Action::SwitchMode(Mode::Filter)]),
+               ("Sort", &[Action::CycleSort, Action::ReverseSort]),
+               ("Add/Edit", &[Action::NewTask, Action::EditSelection]),
+               (
+                  "Mark",
+                  &[
+                     Action::ToggleMark,
+                     Action::ToggleVisualMark,
+                     Action::ToggleMarkAll,
+                  ],
+               ),
+               ("Start/Pause/Kill", START_PAUSE_KILL),
+               ("Restart", RESTART),
+            ],
+         );
+      }
+
+      let visual = self.table_state.is_visual();
+      let hints = key_hints(
+         &self.keymap,
+         &[
+            (
+               "End range",
+               if visual {
+                  &[Action::ToggleVisualMark]
+               } else {
+                  &[]
+               },
+            ),
+            ("Unmark", &[Action::ClearMarks]),
+            ("Start/Pause/Kill", START_PAUSE_KILL),
+            ("Restart", RESTART),
+            ("Remove", &[Action::ApplyToSelection(TaskAction::Remove)]),
+         ],
+      );
+      format!(
+         "{marked} marked{}  {hints}",
+         if visual { " (visual)" } else { "" }
+      )
+   }
+}
+
 impl Component for Home {
    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
       self.command_tx = Some(tx);
       Ok(())
    }
 
-   fn handle_key_event(
-      &mut self,
-      key: crossterm::event::KeyEvent,
-   ) -> color_eyre::Result<Option<Action>> {
-      let KeyEvent {
-         code, modifiers, ..
-      } = key;
-      match modifiers {
-         KeyModifiers::NONE => match code {
-            crossterm::event::KeyCode::Down => {
-               self.next_row();
-               return Ok(None);
-            }
-            crossterm::event::KeyCode::Up => {
-               self.prev_row();
-               return Ok(None);
-            }
-            _ => {}
-         },
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymap = config.keymaps()?.remove(&Mode::Home).unwrap_or_default();
+      self.config = config;
+      Ok(())
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   /// The countdown to the next reconnect ticks while disconnected, and so does the duration of
+   /// running tasks, which may also change their order.
+   fn next_redraw(&self, last_draw: Instant) -> Option<Instant> {
+      let live = self.disconnected.is_some()
+         || ((self.config.columns.contains(&HeaderCell::Duration) || self.sorts_by_duration())
+            && self.tasks.iter().any(|task| task.is_running()));
+      live.then(|| last_draw + LIVE_REDRAW_INTERVAL)
+   }
+
+   fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+      match self.mode {
+         Mode::Search if self.search.handle_key_event(key) => {
+            self.update_tasks();
+            self.select_row(0);
+            self.dirty = true;
+         }
+         Mode::Filter if self.filter_input.handle_key_event(key) => {
+            self.filter_error = None;
+            self.dirty = true;
+         }
          _ => {}
       }
-
       Ok(None)
    }
 
    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
       match action {
-         Action::Tick => {}
-         Action::Render => {}
-         Action::UpdateStatus(state) => {
-            self.tasks = state.tasks.values().cloned().collect();
+         Action::StateChanged(diff) => {
+            diff.apply(&mut self.state);
+            self.update_tasks();
+            self.apply_pending_selection();
          }
-         _ => {}
+         Action::SelectNext => self.next_row(),
+         Action::SelectPrev => self.prev_row(),
+         Action::SelectFirst => self.select_row(0),
+         Action::SelectLast => self.select_row(self.tasks.len().saturating_sub(1)),
+         Action::OpenSelection => {
+            return Ok(self.selected_task().map(|task| Action::OpenDetail(task.id)));
+         }
+         Action::NextGroup => self.select_group(1),
+         Action::PrevGroup => self.select_group(-1),
+         Action::SwitchMode(mode) => self.mode = mode,
+         Action::PendingKeys { count, keys } => self.pending_keys = describe_pending(count, &keys),
+         Action::ClearSearch => {
+            self.dirty = true;
+            self.search.clear();
+            self.update_tasks();
+            if self.mode == Mode::Search {
+               return Ok(Some(Action::SwitchMode(Mode::Home)));
+            }
+         }
+         Action::ApplyFilter => {
+            self.dirty = true;
+            return Ok(self.apply_filter());
+         }
+         Action::ToggleMark => {
+            if let Some(task) = self.selected_task() {
+               self.table_state.toggle_mark(task.id);
+               self.next_row();
+            }
+         }
+         Action::ToggleVisualMark => self.table_state.toggle_visual(&self.tasks),
+         Action::ToggleMarkAll => self.table_state.toggle_all(&self.tasks),
+         Action::ClearMarks => self.table_state.clear_marks(),
+         Action::NewTask => {
+            return Ok(Some(Action::OpenAddTask {
+               group: self.selected_group.clone(),
+               dependencies: self.table_state.marked_ids(&self.tasks),
+            }));
+         }
+         Action::EditSelection => {
+            return Ok(self.selected_task().map(|task| match task.status {
+               TaskStatus::Stashed { .. } | TaskStatus::Queued { .. } => Action::EditTask(task.id),
+               _ => Action::Error(format!(
+                  "Only stashed or queued tasks can be edited (task {}: {})",
+                  task.id,
+                  TaskTable::task_status_to_string(&task.status)
+               )),
+            }));
+         }
+         Action::SelectTask(task_id) => {
+            self.pending_selection = Some(task_id);
+            self.apply_pending_selection();
+         }
+         Action::ApplyToSelection(task_action) => {
+            let marked = self.table_state.marked_ids(&self.tasks);
+            let tasks: Vec<Task> = if marked.is_empty() {
+               self.selected_task().cloned().into_iter().collect()
+            } else {
+               self
+                  .tasks
+                  .iter()
+                  .filter(|task| marked.contains(&task.id))
+                  .cloned()
+                  .collect()
+            };
+            return Ok(self.apply_to_tasks(task_action, tasks));
+         }
+         // The marks are kept until the action runs, so they survive a declined confirmation.
+         Action::ApplyToTasks(_, tasks) => {
+            let marked = self.table_state.marked_ids(&self.tasks);
+            if marked.is_empty()
+               || marked.len() != tasks.len()
+               || !tasks.iter().all(|task| marked.contains(&task.id))
+            {
+               return Ok(None);
+            }
+            self.table_state.clear_marks();
+         }
+         Action::ApplyToTaskIds(task_action, ids) => {
+            let unknown: Vec<usize> = ids
+               .iter()
+               .copied()
+               .filter(|id| !self.state.tasks.contains_key(id))
+               .collect();
+            if !unknown.is_empty() {
+               return Ok(Some(Action::Error(format!(
+                  "There is no {}",
+                  task_list(&unknown)
+               ))));
+            }
+            let tasks = ids
+               .iter()
+               .filter_map(|id| self.state.tasks.get(id))
+               .cloned()
+               .collect();
+            return Ok(self.apply_to_tasks(task_action, tasks));
+         }
+         Action::SetFilter(query) => {
+            self.dirty = true;
+            self.filter_input = TextInputState::new(&query);
+            return Ok(self.apply_filter());
+         }
+         Action::SortTasks(sort) => {
+            self.sort = sort;
+            self.update_tasks();
+         }
+         Action::CycleSort => self.cycle_sort(),
+         Action::ReverseSort => self.reverse_sort(),
+         Action::Clean => {
+            let finished = self.group_task_ids(Task::is_done);
+            if finished.is_empty() {
+               return Ok(Some(Action::Info(
+                  "There are no finished tasks to clean up.".to_string(),
+               )));
+            }
+            return Ok(Some(Self::confirm(
+               self.config.confirm.clean,
+               "Clean up",
+               format!(
+                  "Remove the finished {} of {}?",
+                  task_list(&finished),
+                  self.group_scope()
+               ),
+               Action::CleanGroup(self.selected_group.clone()),
+            )));
+         }
+         Action::Reset => {
+            let tasks = self.group_task_ids(|_| true);
+            return Ok(Some(Self::confirm(
+               self.config.confirm.reset,
+               "Reset",
+               format!(
+                  "Reset {}? This kills and removes {}.",
+                  self.group_scope(),
+                  task_list(&tasks)
+               ),
+               Action::ResetGroup(self.selected_group.clone()),
+            )));
+         }
+         Action::Shutdown => {
+            let running: Vec<usize> = self
+               .state
+               .tasks
+               .values()
+               .filter(|task| task.is_running())
+               .map(|task| task.id)
+               .collect();
+            let message = match running.len() {
+               0 => "Shut down the pueue daemon?".to_string(),
+               _ => format!(
+                  "Shut down the pueue daemon? The running {} will be killed.",
+                  task_list(&running)
+               ),
+            };
+            return Ok(Some(Self::confirm(
+               self.config.confirm.shutdown,
+               "Shut down",
+               message,
+               Action::ShutdownDaemon,
+            )));
+         }
+         Action::Disconnected { error, retry_in } => {
+            self.disconnected = Some((error, Instant::now() + retry_in));
+         }
+         Action::Connected => {
+            self.disconnected = None;
+            self.dirty = true;
+            return Ok(Some(Action::Info("Reconnected to the daemon".to_string())));
+         }
+         Action::DaemonVersion {
+            version,
+            compatible,
+         } => {
+            self.daemon_version = Some((version.clone(), compatible));
+            self.dirty = true;
+            if !compatible {
+               return Ok(Some(Action::Error(format!(
+                  "The daemon speaks protocol v{version}, but pueue-tui is built for v{}. \
+                   Requests may fail, update pueue-tui or restart the daemon with a matching pueue.",
+                  pueue_lib::PROTOCOL_VERSION
+               ))));
+            }
+         }
+         Action::CancelFilter => {
+            let raw = self.filter.as_ref().map_or("", |(raw, _)| raw.as_str());
+            self.filter_input = TextInputState::new(raw);
+            self.filter_error = None;
+            self.dirty = true;
+            return Ok(Some(Action::SwitchMode(Mode::Home)));
+         }
+         _ => return Ok(None),
       }
+      self.dirty = true;
       Ok(None)
    }
 
    fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
-      let [table_area, status_bar_area] =
-         Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+      self.dirty = false;
+      if self.sorts_by_duration() && self.tasks.iter().any(|task| task.is_running()) {
+         self.sort_tasks();
+      }
+      let is_searching = self.mode == Mode::Search || !self.search.value().is_empty();
+      let is_filtering = self.mode == Mode::Filter || self.filter.is_some();
+      let [
+         tabs_area,
+         table_area,
+         filter_area,
+         search_area,
+         status_bar_area,
+      ] = Layout::vertical([
+         Constraint::Length(1),
+         Constraint::Fill(1),
+         Constraint::Length(is_filtering as u16),
+         Constraint::Length(is_searching as u16),
+         Constraint::Length(1),
+      ])
+      .areas(area);
 
-      let table = TaskTable::new(&self.tasks);
-      let status_bar = StatusBar::new("Quit : q");
+      let tabs = GroupTabs::new(&self.state.groups, self.selected_group.as_deref());
+      let table = TaskTable::new(&self.tasks)
+         .generation(self.generation)
+         .highlights(&self.highlights)
+         .sort(if self.search.value().is_empty() {
+            &self.sort
+         } else {
+            &[]
+         })
+         .columns(&self.config.columns)
+         .theme(&self.config.theme);
+      let status_bar = match (&self.filter_error, &self.disconnected) {
+         (Some(err), _) => StatusBar::error(&err.to_string()),
+         (None, Some((error, retry_at))) => {
+            StatusBar::error(&disconnected_message(error, *retry_at))
+         }
+         (None, None) => StatusBar::new(&self.key_hints()),
+      }
+      .pending_keys(&self.pending_keys)
+      .daemon_version(self.daemon_version.as_ref());
 
+      frame.render_widget(tabs, tabs_area);
       frame.render_stateful_widget(table, table_area, &mut self.table_state);
+      if self.disconnected.is_some() {
+         // The last known state stays visible, greyed out as it may be outdated.
+         frame
+            .buffer_mut()
+            .set_style(table_area, Style::new().dark_gray().not_bold());
+      }
+      if is_filtering {
+         let filter = TextInput::new("filter: ").focused(self.mode == Mode::Filter);
+         if self.mode == Mode::Filter {
+            frame.set_cursor_position(filter.cursor_position(filter_area, &self.filter_input));
+         }
+         frame.render_stateful_widget(filter, filter_area, &mut self.filter_input);
+      }
+      if is_searching {
+         let search = TextInput::new("/").focused(self.mode == Mode::Search);
+         if self.mode == Mode::Search {
+            frame.set_cursor_position(search.cursor_position(search_area, &self.search));
+         }
+         frame.render_stateful_widget(search, search_area, &mut self.search);
+         frame.render_widget(
+            Line::from(format!("{} matches", self.tasks.len())).right_aligned(),
+            search_area,
+         );
+      }
       frame.render_widget(status_bar, status_bar_area);
       Ok(())
    }
 }
 
 impl Home {
+   fn update_tasks(&mut self) {
+      if let Some(group) = &self.selected_group
+         && !self.state.groups.contains_key(group)
+      {
+         self.selected_group = None;
+      }
+      let tasks = self.state.tasks.values().filter(|task| {
+         self
+            .selected_group
+            .as_ref()
+            .is_none_or(|g| &task.group == g)
+            && self
+               .filter
+               .as_ref()
+               .is_none_or(|(_, filter)| filter.matches(task))
+      });
+
+      self.generation += 1;
+      self.highlights.clear();
+      let query = self.search.value();
+      if query.is_empty() {
+         self.tasks = tasks.cloned().collect();
+         self.table_state.retain_marks(&self.tasks);
+         self.sort_tasks();
+         return;
+      }
+
+      let mut matches: Vec<(i64, &Task)> = tasks
+         .filter_map(|task| {
+            let (score, column, positions) = Self::search_task(task, query)?;
+            self
+               .highlights
+               .insert(task.id, HashMap::from([(column, positions)]));
+            Some((score, task))
+         })
+         .collect();
+      matches.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.id.cmp(&b.id)));
+      self.tasks = matches.into_iter().map(|(_, task)| task.clone()).collect();
+      self.table_state.retain_marks(&self.tasks);
+      self.table_state.follow_selection(&self.tasks);
+   }
+
+   /// Sort the tasks by the sort keys, keeping the selected task selected.
+   fn sort_tasks(&mut self) {
+      let now = Local::now();
+      let ids: Vec<usize> = self.tasks.iter().map(|task| task.id).collect();
+      self.tasks.sort_by(|a, b| {
+         self
+            .sort
+            .iter()
+            .map(|key| key.compare(a, b, now))
+            .fold(Ordering::Equal, Ordering::then)
+            .then(a.id.cmp(&b.id))
+      });
+      if !self.tasks.iter().map(|task| task.id).eq(ids) {
+         self.generation += 1;
+      }
+      self.table_state.follow_selection(&self.tasks);
+   }
+
+   /// Whether the order of the tasks depends on how long they have been running, which changes
+   /// while they are.
+   fn sorts_by_duration(&self) -> bool {
+      self.search.value().is_empty()
+         && self
+            .sort
+            .iter()
+            .any(|key| key.column == HeaderCell::Duration)
+   }
+
+   /// Sort by the column after the one the tasks are sorted by now, in ascending order.
+   fn cycle_sort(&mut self) {
+      let columns = &self.config.columns;
+      let current = self.sort.first().map_or(HeaderCell::Id, |key| key.column);
+      let next = columns
+         .iter()
+         .position(|&column| column == current)
+         .map_or(0, |i| (i + 1) % columns.len());
+      let Some(&column) = columns.get(next) else {
+         return;
+      };
+      self.sort = vec![SortKey {
+         column,
+         descending: false,
+      }];
+      self.update_tasks();
+   }
+
+   /// Reverse the order of the first sort key, or sort by descending id if there is none.
+   fn reverse_sort(&mut self) {
+      match self.sort.first_mut() {
+         Some(key) => key.descending = !key.descending,
+         None => self.sort.push(SortKey {
+            column: HeaderCell::Id,
+            descending: true,
+         }),
+      }
+      self.update_tasks();
+   }
+
+   /// Find the best fuzzy match of `query` among the searchable columns of a task.
+   fn search_task(task: &Task, query: &str) -> Option<(i64, HeaderCell, Vec<usize>)> {
+      [
+         (HeaderCell::Id, task.id.to_string()),
+         (HeaderCell::Command, task.command.clone()),
+         (HeaderCell::Label, task.label.clone().unwrap_or_default()),
+         (HeaderCell::Path, task.path.to_string_lossy().to_string()),
+      ]
+      .into_iter()
+      .filter_map(|(column, text)| {
+         fuzzy_match(query, &text).map(|m| (m.score, column, m.positions))
+      })
+      .max_by_key(|(score, ..)| *score)
+   }
+
+   fn select_row(&mut self, i: usize) {
+      self.table_state.select_task(&self.tasks, i);
+   }
+
+   /// Move the group selection by `offset` tabs, wrapping around at both ends.
+   fn select_group(&mut self, offset: isize) {
+      let tab_count = self.state.groups.len() + 1;
+      let current = match &self.selected_group {
+         Some(name) => self
+            .state
+            .groups
+            .keys()
+            .position(|g| g == name)
+            .map_or(0, |i| i + 1),
+         None => 0,
+      };
+      let next = (current as isize + offset).rem_euclid(tab_count as isize) as usize;
+      self.selected_group = next
+         .checked_sub(1)
+         .and_then(|i| self.state.groups.keys().nth(i).cloned());
+
+      self.update_tasks();
+      self.select_row(0);
+   }
+
+   /// Ask for confirmation before running `action` if `enabled` is set.
+   fn confirm(enabled: bool, title: &str, message: String, action: Action) -> Action {
+      if !enabled {
+         return action;
+      }
+      Action::RequestConfirmation(Box::new(Confirmation {
+         title: title.to_string(),
+         message,
+         action,
+      }))
+   }
+
+   /// Ids of the tasks in the selected group, or in all groups, which match `predicate`.
+   fn group_task_ids(&self, predicate: impl Fn(&Task) -> bool) -> Vec<usize> {
+      self
+         .state
+         .tasks
+         .values()
+         .filter(|task| {
+            self
+               .selected_group
+               .as_ref()
+               .is_none_or(|group| &task.group == group)
+               && predicate(task)
+         })
+         .map(|task| task.id)
+         .collect()
+   }
+
+   fn group_scope(&self) -> String {
+      match &self.selected_group {
+         Some(group) => format!("group `{group}`"),
+         None => "all groups".to_string(),
+      }
+   }
+
+   fn apply_pending_selection(&mut self) {
+      let Some(task_id) = self.pending_selection else {
+         return;
+      };
+      if let Some(i) = self.tasks.iter().position(|task| task.id == task_id) {
+         self.select_row(i);
+         self.pending_selection = None;
+      }
+   }
+
+   fn selected_task(&self) -> Option<&Task> {
+      let id = self.table_state.selected_id()?;
+      self.tasks.iter().find(|task| task.id == id)
+   }
+
+   /// Select the previous row, wrapping around to the last one.
    fn prev_row(&mut self) {
-      let i = match self.table_state.0.selected() {
-         Some(i) => {
-            if i == 0 {
-               self.tasks.len() - 1
-            } else {
-               i - 1
-            }
-         }
+      let i = match self.table_state.selected() {
+         Some(0) => self.tasks.len().saturating_sub(1),
+         Some(i) => i - 1,
          None => 0,
       };
-      self.table_state.0.select(Some(i));
-      self.table_state.1 = self.table_state.1.position(i * 1);
+      self.select_row(i);
    }
 
+   /// Select the next row, wrapping around to the first one.
    fn next_row(&mut self) {
-      let i = match self.table_state.0.selected() {
-         Some(i) => {
-            if i >= self.tasks.len() - 1 {
-               0
-            } else {
-               i + 1
-            }
-         }
-         None => 0,
+      let i = match self.table_state.selected() {
+         Some(i) if i + 1 < self.tasks.len() => i + 1,
+         _ => 0,
       };
-      self.table_state.0.select(Some(i));
-      self.table_state.1 = self.table_state.1.position(i * 1);
+      self.select_row(i);
    }
 }
+
+/// The status bar text while the daemon can't be reached, counting down to the next attempt.
+fn disconnected_message(error: &str, retry_at: Instant) -> String {
+   let remaining = retry_at.saturating_duration_since(Instant::now());
+   let retry = match remaining.as_secs_f64().ceil() as u64 {
+      0 => "reconnecting...".to_string(),
+      secs => format!("reconnecting in {secs}s"),
+   };
+   format!("Disconnected, {retry}: {}", single_line(error))
+}
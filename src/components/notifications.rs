@@ -0,0 +1,304 @@
+use std::{
+   collections::VecDeque,
+   time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, overlay::Overlay};
+use crate::{
+   action::Action,
+   app::Mode,
+   config::{Config, Keymap},
+   keymap::key_hints,
+};
+
+/// The most messages kept in the history, older ones are dropped.
+const MAX_HISTORY: usize = 500;
+/// The most toasts shown at once.
+const MAX_TOASTS: usize = 3;
+const TOAST_WIDTH: u16 = 48;
+/// The most lines of a message shown in a toast.
+const TOAST_MAX_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+   Info,
+   Error,
+}
+
+impl Severity {
+   /// How long a toast of this severity stays on screen.
+   const fn toast_duration(&self) -> Duration {
+      match self {
+         Severity::Info => Duration::from_secs(4),
+         Severity::Error => Duration::from_secs(10),
+      }
+   }
+
+   const fn color(&self) -> Color {
+      match self {
+         Severity::Info => Color::Green,
+         Severity::Error => Color::Red,
+      }
+   }
+
+   const fn as_str(&self) -> &str {
+      match self {
+         Severity::Info => "Info",
+         Severity::Error => "Error",
+      }
+   }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Message {
+   severity: Severity,
+   text: String,
+   /// How often the message has been received in a row.
+   count: usize,
+   /// When the message has been received last.
+   time: DateTime<Local>,
+   received: Instant,
+}
+
+/// Info and error messages, shown as toasts and kept in a scrollable history.
+pub struct Notifications {
+   /// The received messages, oldest first.
+   messages: VecDeque<Message>,
+   keymap: Keymap,
+   overlay: Overlay,
+   /// Whether messages have been received or cleared since they have been drawn.
+   dirty: bool,
+}
+
+impl Notifications {
+   pub fn new() -> Self {
+      Self {
+         messages: VecDeque::new(),
+         keymap: Keymap::new(),
+         overlay: Overlay::new(Mode::Messages, Action::OpenMessages, Action::CloseMessages),
+         dirty: false,
+      }
+   }
+
+   /// Add a message, or count it if it repeats the latest one.
+   fn push(&mut self, severity: Severity, text: String, now: Instant) {
+      let time = Local::now();
+      self.dirty = true;
+      if let Some(last) = self.messages.back_mut()
+         && last.severity == severity
+         && last.text == text
+      {
+         last.count += 1;
+         last.time = time;
+         last.received = now;
+         return;
+      }
+      self.messages.push_back(Message {
+         severity,
+         text,
+         count: 1,
+         time,
+         received: now,
+      });
+      if self.messages.len() > MAX_HISTORY {
+         self.messages.pop_front();
+      }
+   }
+
+   /// The messages which are still shown as toasts, newest first.
+   fn toasts(&self, now: Instant) -> impl Iterator<Item = &Message> {
+      self
+         .messages
+         .iter()
+         .rev()
+         .filter(move |message| {
+            now.duration_since(message.received) < message.severity.toast_duration()
+         })
+         .take(MAX_TOASTS)
+   }
+
+   /// The history, newest first.
+   fn lines(&self) -> Vec<Line<'static>> {
+      self
+         .messages
+         .iter()
+         .rev()
+         .flat_map(|message| {
+            let mut header = vec![
+               Span::from(message.time.format("%H:%M:%S ").to_string()).dark_gray(),
+               Span::from(format!("{:<5}", message.severity.as_str()))
+                  .fg(message.severity.color())
+                  .bold(),
+            ];
+            if message.count > 1 {
+               header.push(Span::from(format!(" (x{})", message.count)).yellow());
+            }
+            let mut lines = message
+               .text
+               .lines()
+               .map(|line| Line::from(format!("  {line}")))
+               .collect::<Vec<_>>();
+            lines.insert(0, Line::from(header));
+            lines
+         })
+         .collect()
+   }
+
+   fn draw_toasts(&mut self, frame: &mut Frame, area: Rect) {
+      // Below the group tabs and above the status bar.
+      let area = area.inner(Margin::new(1, 1));
+      let width = TOAST_WIDTH.min(area.width);
+      let text_width = width.saturating_sub(2).max(1) as usize;
+      let mut y = area.y;
+      for message in self.toasts(Instant::now()) {
+         let lines = message
+            .text
+            .lines()
+            .map(|line| line.chars().count().div_ceil(text_width).max(1))
+            .sum::<usize>()
+            .clamp(1, TOAST_MAX_LINES) as u16;
+         let height = lines + 2;
+         if y + height > area.bottom() {
+            break;
+         }
+         let toast_area = Rect::new(area.right() - width, y, width, height);
+         y += height;
+
+         let mut title = format!(" {} ", message.severity.as_str());
+         if message.count > 1 {
+            title = format!(" {} (x{}) ", message.severity.as_str(), message.count);
+         }
+         let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().fg(message.severity.color()))
+            .title(title);
+         frame.render_widget(Clear, toast_area);
+         frame.render_widget(
+            Paragraph::new(message.text.as_str())
+               .wrap(Wrap { trim: true })
+               .block(block),
+            toast_area,
+         );
+      }
+   }
+}
+
+impl Component for Notifications {
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymap = config
+         .keymaps()?
+         .remove(&Mode::Messages)
+         .unwrap_or_default();
+      Ok(())
+   }
+
+   fn is_modal(&self) -> bool {
+      self.overlay.is_open()
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty || self.overlay.is_dirty()
+   }
+
+   /// Toasts disappear once they expired.
+   fn next_redraw(&self, last_draw: Instant) -> Option<Instant> {
+      self
+         .toasts(last_draw)
+         .map(|message| message.received + message.severity.toast_duration())
+         .min()
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::Info(message) => self.push(Severity::Info, message, Instant::now()),
+         Action::Error(message) => self.push(Severity::Error, message, Instant::now()),
+         Action::ClearMessages if self.overlay.is_open() => {
+            self.messages.clear();
+            self.dirty = true;
+            self.overlay.scroll_to_top();
+         }
+         action => return Ok(self.overlay.update(&action)),
+      }
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      if !self.overlay.is_open() {
+         self.draw_toasts(frame, area);
+         return Ok(());
+      }
+
+      let mut lines = self.lines();
+      if lines.is_empty() {
+         lines.push(Line::from("No messages").dark_gray());
+      }
+      let popup_area = area.inner(Margin::new(4, 2));
+      let hints = key_hints(
+         &self.keymap,
+         &[
+            ("Scroll", &[Action::ScrollDown, Action::ScrollUp]),
+            ("Clear", &[Action::ClearMessages]),
+            ("Close", &[Action::CloseMessages]),
+         ],
+      );
+      let block = Block::bordered()
+         .title(format!(" Messages ({}) ", self.messages.len()))
+         .title_bottom(Line::from(format!(" {hints} ")).right_aligned())
+         .padding(Padding::horizontal(1));
+      self.overlay.render(frame, popup_area, block, lines);
+      Ok(())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_collapse_and_expire() {
+      let mut notifications = Notifications::new();
+      let start = Instant::now();
+      for _ in 0..3 {
+         notifications.push(Severity::Error, "Failed to fetch status".to_string(), start);
+      }
+      notifications.push(Severity::Info, "Task 1 started".to_string(), start);
+      notifications.push(
+         Severity::Error,
+         "Failed to fetch status".to_string(),
+         start + Duration::from_secs(5),
+      );
+
+      let summary: Vec<(Severity, usize)> = notifications
+         .messages
+         .iter()
+         .map(|message| (message.severity, message.count))
+         .collect();
+      assert_eq!(
+         summary,
+         [
+            (Severity::Error, 3),
+            (Severity::Info, 1),
+            (Severity::Error, 1)
+         ]
+      );
+
+      // Info toasts expire sooner than error toasts.
+      let toasts: Vec<&str> = notifications
+         .toasts(start + Duration::from_secs(6))
+         .map(|message| message.text.as_str())
+         .collect();
+      assert_eq!(toasts, ["Failed to fetch status", "Failed to fetch status"]);
+      assert_eq!(
+         notifications
+            .toasts(start + Duration::from_secs(20))
+            .count(),
+         0
+      );
+   }
+}
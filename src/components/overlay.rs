@@ -0,0 +1,154 @@
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{action::Action, app::Mode};
+
+/// A scrollable popup which is opened on top of another mode and returns to it when closed.
+///
+/// Components like [`Help`](super::help::Help) hand their actions to [`Overlay::update`] and
+/// their lines to [`Overlay::render`].
+pub struct Overlay {
+   /// The mode while the overlay is open.
+   mode: Mode,
+   open_action: Action,
+   close_action: Action,
+   open: bool,
+   /// The current mode, which becomes the return mode on open.
+   current_mode: Mode,
+   return_mode: Mode,
+   scroll: usize,
+   page_height: usize,
+   /// The number of lines drawn last time.
+   line_count: usize,
+   dirty: bool,
+}
+
+impl Overlay {
+   pub fn new(mode: Mode, open_action: Action, close_action: Action) -> Self {
+      Self {
+         mode,
+         open_action,
+         close_action,
+         open: false,
+         current_mode: Mode::default(),
+         return_mode: Mode::default(),
+         scroll: 0,
+         page_height: 0,
+         line_count: 0,
+         dirty: false,
+      }
+   }
+
+   pub fn is_open(&self) -> bool {
+      self.open
+   }
+
+   /// Whether the overlay has been opened, closed or scrolled since it has been drawn.
+   pub fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   /// The mode the overlay has been opened from.
+   pub fn return_mode(&self) -> Mode {
+      self.return_mode
+   }
+
+   /// Scroll back to the top, e.g. after the lines changed.
+   pub fn scroll_to_top(&mut self) {
+      self.scroll = 0;
+      self.dirty = true;
+   }
+
+   /// Open, close or scroll the overlay, returning the mode to switch to.
+   pub fn update(&mut self, action: &Action) -> Option<Action> {
+      match action {
+         Action::SwitchMode(mode) => self.current_mode = *mode,
+         action if !self.open && *action == self.open_action => {
+            self.open = true;
+            self.return_mode = self.current_mode;
+            self.scroll = 0;
+            self.dirty = true;
+            return Some(Action::SwitchMode(self.mode));
+         }
+         _ if !self.open => {}
+         action if *action == self.close_action => {
+            self.open = false;
+            self.dirty = true;
+            return Some(Action::SwitchMode(self.return_mode));
+         }
+         Action::ScrollUp => self.scroll_by(-1),
+         Action::ScrollDown => self.scroll_by(1),
+         Action::ScrollPageUp => self.scroll_by(-(self.page_height as isize)),
+         Action::ScrollPageDown => self.scroll_by(self.page_height as isize),
+         Action::ScrollTop => self.scroll_to_top(),
+         Action::ScrollBottom => {
+            self.scroll = self.max_scroll();
+            self.dirty = true;
+         }
+         _ => {}
+      }
+      None
+   }
+
+   fn max_scroll(&self) -> usize {
+      self.line_count.saturating_sub(self.page_height)
+   }
+
+   fn scroll_by(&mut self, delta: isize) {
+      self.scroll = self
+         .scroll
+         .saturating_add_signed(delta)
+         .min(self.max_scroll());
+      self.dirty = true;
+   }
+
+   /// Draw the lines in the block, scrolled so that the last page stays full.
+   pub fn render(&mut self, frame: &mut Frame, area: Rect, block: Block, lines: Vec<Line>) {
+      self.dirty = false;
+      self.line_count = lines.len();
+      self.page_height = block.inner(area).height as usize;
+      self.scroll = self.scroll.min(self.max_scroll());
+
+      frame.render_widget(Clear, area);
+      frame.render_widget(
+         Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll as u16, 0)),
+         area,
+      );
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_open_scroll_close() {
+      let mut overlay = Overlay::new(Mode::Help, Action::OpenHelp, Action::CloseHelp);
+      assert_eq!(overlay.update(&Action::SwitchMode(Mode::Detail)), None);
+      assert_eq!(overlay.update(&Action::ScrollDown), None);
+      assert_eq!(
+         overlay.update(&Action::OpenHelp),
+         Some(Action::SwitchMode(Mode::Help))
+      );
+      assert!(overlay.is_open());
+      assert_eq!(overlay.return_mode(), Mode::Detail);
+
+      overlay.line_count = 30;
+      overlay.page_height = 10;
+      overlay.update(&Action::ScrollPageDown);
+      assert_eq!(overlay.scroll, 10);
+      overlay.update(&Action::ScrollBottom);
+      assert_eq!(overlay.scroll, 20);
+      overlay.update(&Action::ScrollDown);
+      assert_eq!(overlay.scroll, 20);
+
+      assert_eq!(
+         overlay.update(&Action::CloseHelp),
+         Some(Action::SwitchMode(Mode::Detail))
+      );
+      assert!(!overlay.is_open());
+   }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, widgets::*};
+
+use super::Component;
+use crate::{
+   action::Action,
+   app::Mode,
+   config::Config,
+   keymap::{KeyTrie, describe_pending, display_keys},
+};
+
+/// Popup listing the keys which continue an unfinished key sequence.
+#[derive(Default)]
+pub struct WhichKey {
+   keymaps: HashMap<Mode, KeyTrie>,
+   mode: Mode,
+   count: Option<usize>,
+   pending: Vec<KeyEvent>,
+   dirty: bool,
+}
+
+impl WhichKey {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   /// The continuations of the pending keys, sorted by key.
+   fn continuations(&self) -> Vec<(String, String)> {
+      let Some(node) = self
+         .keymaps
+         .get(&self.mode)
+         .and_then(|keymap| keymap.get(&self.pending))
+      else {
+         return Vec::new();
+      };
+      let mut continuations: Vec<_> = node
+         .children()
+         .map(|(key, child)| {
+            let description = match (child.action(), child.children().count()) {
+               (Some(action), 0) => action.description(),
+               (Some(action), n) => format!("{}, +{n} more", action.description()),
+               (None, n) => format!("+{n} more"),
+            };
+            (display_keys(&[*key]), description)
+         })
+         .collect();
+      continuations.sort();
+      continuations
+   }
+}
+
+impl Component for WhichKey {
+   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+      self.keymaps = config
+         .keymaps()?
+         .iter()
+         .map(|(mode, keymap)| (*mode, KeyTrie::new(keymap)))
+         .collect();
+      Ok(())
+   }
+
+   fn is_dirty(&self) -> bool {
+      self.dirty
+   }
+
+   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+      match action {
+         Action::SwitchMode(mode) => self.mode = mode,
+         Action::PendingKeys { count, keys } => {
+            self.count = count;
+            self.pending = keys;
+            self.dirty = true;
+         }
+         _ => {}
+      }
+      Ok(None)
+   }
+
+   fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+      self.dirty = false;
+      if self.pending.is_empty() {
+         return Ok(());
+      }
+      let continuations = self.continuations();
+      if continuations.is_empty() {
+         return Ok(());
+      }
+
+      let key_width = continuations
+         .iter()
+         .map(|(key, _)| key.chars().count())
+         .max()
+         .unwrap_or_default();
+      let lines: Vec<Line> = continuations
+         .into_iter()
+         .map(|(key, description)| {
+            Line::from(vec![
+               Span::from(format!("{key:<key_width$}")).yellow().bold(),
+               Span::from("  "),
+               Span::from(description),
+            ])
+         })
+         .collect();
+      let title = format!(" {} ", describe_pending(self.count, &self.pending));
+      let width = lines
+         .iter()
+         .map(Line::width)
+         .chain([title.chars().count()])
+         .max()
+         .unwrap_or_default() as u16
+         + 4;
+
+      // Above the status bar in the bottom right corner.
+      let [area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+      let [_, popup_area] = Layout::vertical([
+         Constraint::Fill(1),
+         Constraint::Length(lines.len() as u16 + 2),
+      ])
+      .areas(area);
+      let [_, popup_area] =
+         Layout::horizontal([Constraint::Fill(1), Constraint::Length(width)]).areas(popup_area);
+
+      let block = Block::bordered()
+         .title(title)
+         .padding(Padding::horizontal(1));
+      frame.render_widget(Clear, popup_area);
+      frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+      Ok(())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   use crate::config::parse_key_sequence;
+
+   #[test]
+   fn test_continuations() {
+      let keymap = [
+         ("<g><g>", Action::SelectFirst),
+         ("<g><e>", Action::SelectLast),
+         ("<g><t>", Action::NextGroup),
+         ("<g><t><t>", Action::PrevGroup),
+         ("<g><x><x>", Action::Quit),
+      ]
+      .into_iter()
+      .map(|(raw, action)| (parse_key_sequence(raw).unwrap(), action))
+      .collect();
+      let mut which_key = WhichKey {
+         keymaps: HashMap::from([(Mode::Home, KeyTrie::new(&keymap))]),
+         pending: parse_key_sequence("<g>").unwrap(),
+         ..WhichKey::default()
+      };
+
+      let continuation = |key: &str, description: &str| (key.to_string(), description.to_string());
+      assert_eq!(
+         which_key.continuations(),
+         vec![
+            continuation("e", "Last task"),
+            continuation("g", "First task"),
+            continuation("t", "Next group, +1 more"),
+            continuation("x", "+1 more"),
+         ]
+      );
+
+      which_key.mode = Mode::Detail;
+      assert_eq!(which_key.continuations(), vec![]);
+   }
+}
@@ -0,0 +1,760 @@
+use std::{
+   collections::HashMap,
+   fs,
+   path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{
+   action::{Action, TaskAction},
+   app::Mode,
+   keymap::normalize,
+   widgets::task_table::HeaderCell,
+};
+
+/// Settings of pueue-tui, read from `config.yml` in the `pueue-tui` config directory.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+   /// Seconds between fetching the status from the daemon while tasks run or keys are pressed.
+   #[serde(deserialize_with = "positive")]
+   pub status_reload_rate: f64,
+   /// Seconds between fetching the status while nothing runs or the terminal isn't focused.
+   #[serde(deserialize_with = "positive")]
+   pub idle_status_reload_rate: f64,
+   /// The most frames drawn per second. The screen is only drawn when something changed.
+   #[serde(deserialize_with = "positive")]
+   pub frame_rate: f64,
+   #[serde(deserialize_with = "positive")]
+   pub tick_rate: f64,
+   /// How long to wait for the next key of a sequence, in seconds.
+   #[serde(deserialize_with = "positive")]
+   pub key_timeout: f64,
+   pub mouse: bool,
+   pub paste: bool,
+   pub confirm: Confirmations,
+   /// The columns of the task table in this order. Columns without any data are hidden.
+   pub columns: Vec<HeaderCell>,
+   pub theme: Theme,
+   /// Key bindings per mode, added to or replacing the default ones. `null` removes a binding.
+   pub keymaps: HashMap<Mode, Bindings>,
+}
+
+impl Default for Config {
+   fn default() -> Self {
+      Self {
+         status_reload_rate: 1.0,
+         idle_status_reload_rate: 5.0,
+         frame_rate: 60.0,
+         tick_rate: 4.0,
+         key_timeout: 1.0,
+         mouse: true,
+         paste: false,
+         confirm: Confirmations::default(),
+         columns: vec![
+            HeaderCell::Id,
+            HeaderCell::Status,
+            HeaderCell::Priority,
+            HeaderCell::EnqueueAt,
+            HeaderCell::Dependencies,
+            HeaderCell::Label,
+            HeaderCell::Command,
+            HeaderCell::Path,
+            HeaderCell::Start,
+            HeaderCell::End,
+            HeaderCell::Duration,
+         ],
+         theme: Theme::default(),
+         keymaps: HashMap::new(),
+      }
+   }
+}
+
+impl Config {
+   /// Load the config from `path`, or from the default location if no path is given.
+   ///
+   /// A missing file at the default location is not an error, the defaults are used then.
+   pub fn load(path: Option<&Path>) -> color_eyre::Result<Self> {
+      let path = match path {
+         Some(path) => path.to_path_buf(),
+         None => match Self::default_path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Self::default()),
+         },
+      };
+      let content = fs::read_to_string(&path)
+         .wrap_err_with(|| format!("Failed to read the config file {}", path.display()))?;
+      Self::parse(&content).map_err(|e| eyre!("Invalid config file {}: {e}", path.display()))
+   }
+
+   pub fn default_path() -> Option<PathBuf> {
+      dirs::config_dir().map(|dir| dir.join("pueue-tui").join("config.yml"))
+   }
+
+   /// The default key bindings merged with the configured ones.
+   ///
+   /// Fails with a list of all conflicting bindings, so they can be fixed at once.
+   pub fn keymaps(&self) -> color_eyre::Result<HashMap<Mode, Keymap>> {
+      let mut keymaps: HashMap<Mode, Keymap> = HashMap::new();
+      for (mode, raw, action) in default_bindings() {
+         let keys = parse_key_sequence(raw).map_err(|e| eyre!(e))?;
+         keymaps.entry(mode).or_default().insert(keys, action);
+      }
+      for (mode, bindings) in &self.keymaps {
+         let keymap = keymaps.entry(*mode).or_default();
+         for (keys, action) in &bindings.0 {
+            match action {
+               Some(action) => keymap.insert(keys.keys.clone(), action.clone()),
+               None => keymap.remove(&keys.keys),
+            };
+         }
+      }
+
+      let mut conflicts: Vec<String> = keymaps
+         .iter()
+         .flat_map(|(mode, keymap)| keymap_conflicts(*mode, keymap))
+         .collect();
+      if !conflicts.is_empty() {
+         conflicts.sort();
+         bail!("Conflicting key bindings:\n  {}", conflicts.join("\n  "));
+      }
+      Ok(keymaps)
+   }
+
+   fn parse(content: &str) -> Result<Self, serde_yaml::Error> {
+      if content.trim().is_empty() {
+         return Ok(Self::default());
+      }
+      serde_yaml::from_str(content)
+   }
+}
+
+/// Key sequences of one mode and the actions they trigger.
+pub type Keymap = HashMap<Vec<KeyEvent>, Action>;
+
+fn default_bindings() -> Vec<(Mode, &'static str, Action)> {
+   use Action::*;
+   use Mode::{
+      AddTask as AddTaskMode, Command, Confirm, Detail, Filter, Help, Home, Messages, Search,
+   };
+
+   let mut bindings = vec![
+      (Home, "<q>", Quit),
+      (Home, "<Ctrl-d>", Quit),
+      (Home, "<down>", SelectNext),
+      (Home, "<up>", SelectPrev),
+      (Home, "<j>", SelectNext),
+      (Home, "<k>", SelectPrev),
+      (Home, "<g><g>", SelectFirst),
+      (Home, "<home>", SelectFirst),
+      (Home, "<end>", SelectLast),
+      (Home, "<shift-g>", SelectLast),
+      (Home, "<enter>", OpenSelection),
+      (Home, "<tab>", NextGroup),
+      (Home, "<backtab>", PrevGroup),
+      (Home, "</>", SwitchMode(Search)),
+      (Home, "<esc>", ClearSearch),
+      (Home, "<f>", SwitchMode(Filter)),
+      (Home, "<o>", CycleSort),
+      (Home, "<shift-o>", ReverseSort),
+      (Home, "<s>", ApplyToSelection(TaskAction::Start)),
+      (Home, "<p>", ApplyToSelection(TaskAction::Pause)),
+      (Home, "<x>", ApplyToSelection(TaskAction::Kill)),
+      (Home, "<r>", ApplyToSelection(TaskAction::Restart)),
+      (
+         Home,
+         "<shift-r>",
+         ApplyToSelection(TaskAction::RestartAsNew),
+      ),
+      (Home, "<shift-s>", ApplyToSelection(TaskAction::Stash)),
+      (Home, "<shift-e>", ApplyToSelection(TaskAction::Enqueue)),
+      (Home, "<shift-d>", ApplyToSelection(TaskAction::Remove)),
+      (Home, "<space>", ToggleMark),
+      (Home, "<shift-v>", ToggleVisualMark),
+      (Home, "<*>", ToggleMarkAll),
+      (Home, "<u>", ClearMarks),
+      (Home, "<a>", NewTask),
+      (Home, "<e>", EditSelection),
+      (Home, "<c>", Clean),
+      (Home, "<shift-x>", Reset),
+      (Home, "<shift-q>", Shutdown),
+      (Home, "<?>", OpenHelp),
+      (Home, "<:>", OpenCommandLine),
+      (Home, "<ctrl-p>", OpenPalette),
+      (Home, "<m>", OpenMessages),
+      (Detail, "<q>", SwitchMode(Home)),
+      (Detail, "<esc>", SwitchMode(Home)),
+      (Detail, "<Ctrl-d>", Quit),
+      (Detail, "<tab>", ToggleDetailView),
+      (Detail, "<?>", OpenHelp),
+      (Detail, "<m>", OpenMessages),
+      (Help, "<?>", CloseHelp),
+      (Messages, "<m>", CloseMessages),
+      (Messages, "<shift-c>", ClearMessages),
+      (Search, "<esc>", ClearSearch),
+      (Search, "<enter>", SwitchMode(Home)),
+      (Search, "<down>", SelectNext),
+      (Search, "<up>", SelectPrev),
+      (Filter, "<esc>", CancelFilter),
+      (Filter, "<enter>", ApplyFilter),
+      (Filter, "<down>", SelectNext),
+      (Filter, "<up>", SelectPrev),
+      (AddTaskMode, "<esc>", SwitchMode(Home)),
+      (AddTaskMode, "<enter>", SubmitAddTask),
+      (AddTaskMode, "<tab>", NextField),
+      (AddTaskMode, "<down>", NextField),
+      (AddTaskMode, "<backtab>", PrevField),
+      (AddTaskMode, "<up>", PrevField),
+      (Command, "<esc>", SwitchMode(Home)),
+      (Command, "<enter>", SubmitCommand),
+      (Command, "<tab>", CompleteCommand),
+      (Command, "<backtab>", PrevCompletion),
+      (Command, "<down>", NextCompletion),
+      (Command, "<up>", PrevCompletion),
+      (Command, "<ctrl-n>", NextCompletion),
+      (Command, "<ctrl-p>", PrevCompletion),
+      (Confirm, "<y>", ConfirmAccept),
+      (Confirm, "<n>", ConfirmReject),
+      (Confirm, "<esc>", ConfirmReject),
+      (Confirm, "<q>", ConfirmReject),
+      (Confirm, "<tab>", ConfirmToggle),
+      (Confirm, "<backtab>", ConfirmToggle),
+      (Confirm, "<left>", ConfirmToggle),
+      (Confirm, "<right>", ConfirmToggle),
+      (Confirm, "<h>", ConfirmToggle),
+      (Confirm, "<l>", ConfirmToggle),
+      (Confirm, "<enter>", ConfirmSubmit),
+   ];
+   bindings.extend(scroll_bindings(Detail));
+   bindings.extend(overlay_bindings(Help, CloseHelp));
+   bindings.extend(overlay_bindings(Messages, CloseMessages));
+   bindings
+}
+
+/// Keys to scroll the text of a mode.
+fn scroll_bindings(mode: Mode) -> Vec<(Mode, &'static str, Action)> {
+   use Action::*;
+
+   vec![
+      (mode, "<up>", ScrollUp),
+      (mode, "<down>", ScrollDown),
+      (mode, "<k>", ScrollUp),
+      (mode, "<j>", ScrollDown),
+      (mode, "<pageup>", ScrollPageUp),
+      (mode, "<pagedown>", ScrollPageDown),
+      (mode, "<home>", ScrollTop),
+      (mode, "<end>", ScrollBottom),
+   ]
+}
+
+/// Keys of a scrollable overlay, closed by `close`.
+fn overlay_bindings(mode: Mode, close: Action) -> Vec<(Mode, &'static str, Action)> {
+   let mut bindings = vec![
+      (mode, "<q>", close.clone()),
+      (mode, "<esc>", close),
+      (mode, "<Ctrl-d>", Action::Quit),
+   ];
+   bindings.extend(scroll_bindings(mode));
+   bindings
+}
+
+/// Describe the bindings of a mode which can't work as intended.
+fn keymap_conflicts(mode: Mode, keymap: &Keymap) -> Vec<String> {
+   let mut conflicts = Vec::new();
+   for (keys, action) in keymap {
+      // Typed characters go into the text input of these modes.
+      if mode.has_text_input()
+         && matches!(keys[0].code, KeyCode::Char(_))
+         && (keys[0].modifiers - KeyModifiers::SHIFT).is_empty()
+      {
+         conflicts.push(format!(
+            "{mode:?}: `{}` ({action:?}) starts with a key which is needed for typing",
+            format_key_sequence(keys)
+         ));
+      }
+   }
+   conflicts
+}
+
+/// Which actions ask for confirmation before they are sent to the daemon.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Confirmations {
+   pub kill: bool,
+   pub remove: bool,
+   pub clean: bool,
+   pub reset: bool,
+   pub shutdown: bool,
+}
+
+impl Default for Confirmations {
+   fn default() -> Self {
+      Self {
+         kill: true,
+         remove: true,
+         clean: true,
+         reset: true,
+         shutdown: true,
+      }
+   }
+}
+
+/// Styles of the task table.
+///
+/// Each style is written as `{ fg: red, bg: "#1e1e1e", modifiers: [bold] }`, all keys optional.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+   #[serde(deserialize_with = "style")]
+   pub header: Style,
+   #[serde(deserialize_with = "style")]
+   pub selected_row: Style,
+   #[serde(deserialize_with = "style")]
+   pub marked_row: Style,
+   /// Characters matched by the search.
+   #[serde(deserialize_with = "style")]
+   pub search_match: Style,
+   #[serde(deserialize_with = "style")]
+   pub status_queued: Style,
+   #[serde(deserialize_with = "style")]
+   pub status_running: Style,
+   #[serde(deserialize_with = "style")]
+   pub status_success: Style,
+   #[serde(deserialize_with = "style")]
+   pub status_failed: Style,
+}
+
+impl Default for Theme {
+   fn default() -> Self {
+      Self {
+         header: Style::new().bold(),
+         selected_row: Style::new().on_black(),
+         marked_row: Style::new().bold().on_dark_gray(),
+         search_match: Style::new().yellow().bold(),
+         status_queued: Style::new().yellow().bold(),
+         status_running: Style::new().green().bold(),
+         status_success: Style::new().green().bold(),
+         status_failed: Style::new().red().bold(),
+      }
+   }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleConfig {
+   fg: Option<Color>,
+   bg: Option<Color>,
+   #[serde(default)]
+   modifiers: Vec<StyleModifier>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StyleModifier {
+   Bold,
+   Dim,
+   Italic,
+   Underlined,
+   Reversed,
+}
+
+fn style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Style, D::Error> {
+   let config = StyleConfig::deserialize(deserializer)?;
+   let modifier = config
+      .modifiers
+      .iter()
+      .fold(Modifier::empty(), |acc, modifier| {
+         acc | match modifier {
+            StyleModifier::Bold => Modifier::BOLD,
+            StyleModifier::Dim => Modifier::DIM,
+            StyleModifier::Italic => Modifier::ITALIC,
+            StyleModifier::Underlined => Modifier::UNDERLINED,
+            StyleModifier::Reversed => Modifier::REVERSED,
+         }
+      });
+   let mut style = Style::new().add_modifier(modifier);
+   style.fg = config.fg;
+   style.bg = config.bg;
+   Ok(style)
+}
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+   let value = f64::deserialize(deserializer)?;
+   if value > 0.0 && value.is_finite() {
+      Ok(value)
+   } else {
+      Err(de::Error::custom(format!(
+         "expected a positive number, got {value}"
+      )))
+   }
+}
+
+/// A key sequence like `<Ctrl-d>` or `<g><g>`, parsed with [`parse_key_sequence`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence {
+   pub keys: Vec<KeyEvent>,
+   /// How the sequence is written in the config.
+   pub raw: String,
+}
+
+impl<'de> Deserialize<'de> for KeySequence {
+   fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+      let raw = String::deserialize(deserializer)?;
+      parse_key_sequence(&raw)
+         .map(|keys| KeySequence {
+            keys,
+            raw: raw.clone(),
+         })
+         .map_err(|e| de::Error::custom(format!("invalid key sequence `{raw}`: {e}")))
+   }
+}
+
+/// The configured key bindings of a mode in the order of the config file, `None` removes one.
+///
+/// Different spellings of the same sequence, like `<ctrl-d>` and `<Ctrl-D>`, are rejected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bindings(pub Vec<(KeySequence, Option<Action>)>);
+
+impl<'de> Deserialize<'de> for Bindings {
+   fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+      struct BindingsVisitor;
+
+      impl<'de> de::Visitor<'de> for BindingsVisitor {
+         type Value = Bindings;
+
+         fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a map of key sequences to actions")
+         }
+
+         fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut bindings: Vec<(KeySequence, Option<Action>)> = Vec::new();
+            while let Some((keys, action)) = map.next_entry::<KeySequence, Option<Action>>()? {
+               if let Some((other, _)) = bindings.iter().find(|(other, _)| other.keys == keys.keys)
+               {
+                  return Err(de::Error::custom(format!(
+                     "`{}` and `{}` are the same key sequence",
+                     other.raw, keys.raw
+                  )));
+               }
+               bindings.push((keys, action));
+            }
+            Ok(Bindings(bindings))
+         }
+      }
+
+      deserializer.deserialize_map(BindingsVisitor)
+   }
+}
+
+fn parse_key_event(raw: &str) -> color_eyre::Result<KeyEvent, String> {
+   let raw_lower = raw.to_ascii_lowercase();
+   let (remaining, modifiers) = extract_modifiers(&raw_lower);
+   parse_key_code_with_modifiers(remaining, modifiers)
+}
+
+fn extract_modifiers(raw: &str) -> (&str, KeyModifiers) {
+   let mut modifiers = KeyModifiers::empty();
+   let mut current = raw;
+
+   loop {
+      match current {
+         rest if rest.starts_with("ctrl-") => {
+            modifiers.insert(KeyModifiers::CONTROL);
+            current = &rest[5..];
+         }
+         rest if rest.starts_with("alt-") => {
+            modifiers.insert(KeyModifiers::ALT);
+            current = &rest[4..];
+         }
+         rest if rest.starts_with("shift-") => {
+            modifiers.insert(KeyModifiers::SHIFT);
+            current = &rest[6..];
+         }
+         _ => break, // break out of the loop if no known prefix is detected
+      };
+   }
+
+   (current, modifiers)
+}
+
+fn parse_key_code_with_modifiers(
+   raw: &str,
+   mut modifiers: KeyModifiers,
+) -> color_eyre::Result<KeyEvent, String> {
+   let c = match raw {
+      "esc" => KeyCode::Esc,
+      "enter" => KeyCode::Enter,
+      "left" => KeyCode::Left,
+      "right" => KeyCode::Right,
+      "up" => KeyCode::Up,
+      "down" => KeyCode::Down,
+      "home" => KeyCode::Home,
+      "end" => KeyCode::End,
+      "pageup" => KeyCode::PageUp,
+      "pagedown" => KeyCode::PageDown,
+      "backtab" => {
+         modifiers.insert(KeyModifiers::SHIFT);
+         KeyCode::BackTab
+      }
+      "backspace" => KeyCode::Backspace,
+      "delete" => KeyCode::Delete,
+      "insert" => KeyCode::Insert,
+      "f1" => KeyCode::F(1),
+      "f2" => KeyCode::F(2),
+      "f3" => KeyCode::F(3),
+      "f4" => KeyCode::F(4),
+      "f5" => KeyCode::F(5),
+      "f6" => KeyCode::F(6),
+      "f7" => KeyCode::F(7),
+      "f8" => KeyCode::F(8),
+      "f9" => KeyCode::F(9),
+      "f10" => KeyCode::F(10),
+      "f11" => KeyCode::F(11),
+      "f12" => KeyCode::F(12),
+      "space" => KeyCode::Char(' '),
+      "hyphen" => KeyCode::Char('-'),
+      "minus" => KeyCode::Char('-'),
+      "tab" => KeyCode::Tab,
+      c if c.len() == 1 => {
+         let mut c = c.chars().next().unwrap();
+         if modifiers.contains(KeyModifiers::SHIFT) {
+            c = c.to_ascii_uppercase();
+         }
+         KeyCode::Char(c)
+      }
+      _ => return Err(format!("Unable to parse {raw}")),
+   };
+   Ok(KeyEvent::new(c, modifiers))
+}
+
+pub fn key_event_to_string(key_event: &KeyEvent) -> String {
+   let char;
+   let key_code = match key_event.code {
+      KeyCode::Backspace => "backspace",
+      KeyCode::Enter => "enter",
+      KeyCode::Left => "left",
+      KeyCode::Right => "right",
+      KeyCode::Up => "up",
+      KeyCode::Down => "down",
+      KeyCode::Home => "home",
+      KeyCode::End => "end",
+      KeyCode::PageUp => "pageup",
+      KeyCode::PageDown => "pagedown",
+      KeyCode::Tab => "tab",
+      KeyCode::BackTab => "backtab",
+      KeyCode::Delete => "delete",
+      KeyCode::Insert => "insert",
+      KeyCode::F(c) => {
+         char = format!("f{c}");
+         &char
+      }
+      KeyCode::Char(' ') => "space",
+      KeyCode::Char(c) => {
+         char = c.to_string();
+         &char
+      }
+      KeyCode::Esc => "esc",
+      KeyCode::Null => "",
+      KeyCode::CapsLock => "",
+      KeyCode::Menu => "",
+      KeyCode::ScrollLock => "",
+      KeyCode::Media(_) => "",
+      KeyCode::NumLock => "",
+      KeyCode::PrintScreen => "",
+      KeyCode::Pause => "",
+      KeyCode::KeypadBegin => "",
+      KeyCode::Modifier(_) => "",
+   };
+
+   let mut modifiers = Vec::with_capacity(3);
+
+   if key_event.modifiers.intersects(KeyModifiers::CONTROL) {
+      modifiers.push("ctrl");
+   }
+
+   if key_event.modifiers.intersects(KeyModifiers::SHIFT) {
+      modifiers.push("shift");
+   }
+
+   if key_event.modifiers.intersects(KeyModifiers::ALT) {
+      modifiers.push("alt");
+   }
+
+   let mut key = modifiers.join("-");
+
+   if !key.is_empty() {
+      key.push('-');
+   }
+   key.push_str(key_code);
+
+   key
+}
+
+/// Format keys like they are written in the config, e.g. `<ctrl-d>` or `<g><g>`.
+pub fn format_key_sequence(keys: &[KeyEvent]) -> String {
+   keys
+      .iter()
+      .map(|key| format!("<{}>", key_event_to_string(key)))
+      .collect()
+}
+
+pub fn parse_key_sequence(raw: &str) -> color_eyre::Result<Vec<KeyEvent>, String> {
+   if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
+      return Err(format!("Unable to parse `{}`", raw));
+   }
+   let raw = if !raw.contains("><") {
+      let raw = raw.strip_prefix('<').unwrap_or(raw);
+      raw.strip_prefix('>').unwrap_or(raw)
+   } else {
+      raw
+   };
+   let sequences = raw
+      .split("><")
+      .map(|seq| {
+         if let Some(s) = seq.strip_prefix('<') {
+            s
+         } else if let Some(s) = seq.strip_suffix('>') {
+            s
+         } else {
+            seq
+         }
+      })
+      .collect::<Vec<_>>();
+
+   sequences
+      .into_iter()
+      .map(|raw| parse_key_event(raw).map(normalize))
+      .collect()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_parse_config() {
+      let config = Config::parse(
+         r##"
+status_reload_rate: 0.5
+mouse: false
+confirm:
+  kill: false
+columns: [Id, Status, Command]
+theme:
+  selected_row: { bg: "#303030", modifiers: [bold] }
+keymaps:
+  Home:
+    "<Ctrl-k>": !ApplyToSelection Kill
+    "<g><g>": Quit
+"##,
+      )
+      .unwrap();
+
+      assert_eq!(config.status_reload_rate, 0.5);
+      assert!(!config.mouse);
+      assert!(!config.confirm.kill && config.confirm.remove);
+      assert_eq!(
+         config.columns,
+         vec![HeaderCell::Id, HeaderCell::Status, HeaderCell::Command]
+      );
+      assert_eq!(
+         config.theme.selected_row,
+         Style::new().bg(Color::Rgb(0x30, 0x30, 0x30)).bold()
+      );
+      assert_eq!(config.theme.header, Theme::default().header);
+
+      let home: Vec<(&str, Option<&Action>)> = config.keymaps[&Mode::Home]
+         .0
+         .iter()
+         .map(|(keys, action)| (keys.raw.as_str(), action.as_ref()))
+         .collect();
+      assert_eq!(
+         home,
+         [
+            (
+               "<Ctrl-k>",
+               Some(&Action::ApplyToSelection(TaskAction::Kill))
+            ),
+            ("<g><g>", Some(&Action::Quit)),
+         ]
+      );
+
+      assert_eq!(Config::parse("\n").unwrap(), Config::default());
+   }
+
+   #[test]
+   fn test_parse_config_errors() {
+      let error = |content| Config::parse(content).unwrap_err().to_string();
+
+      assert!(error("mouse: true\nfram_rate: 30\n").contains("unknown field `fram_rate`"));
+      assert!(error("mouse: true\nfram_rate: 30\n").contains("line 2"));
+      assert!(error("tick_rate: 0\n").contains("expected a positive number"));
+      assert!(error("keymaps:\n  Home:\n    \"<ctrl-foo>\": Quit\n").contains("line 3"));
+      assert!(error("theme:\n  header: { fg: nope }\n").contains("line 2"));
+      let duplicate = error("keymaps:\n  Home:\n    \"<ctrl-d>\": Quit\n    \"<Ctrl-D>\": Quit\n");
+      assert!(
+         duplicate.contains("`<ctrl-d>` and `<Ctrl-D>` are the same key sequence"),
+         "{duplicate}"
+      );
+      assert!(duplicate.starts_with("keymaps.Home: "), "{duplicate}");
+      // SHIFT makes no difference for characters without case.
+      assert!(
+         error("keymaps:\n  Help:\n    \"<?>\": Quit\n    \"<shift-?>\": CloseHelp\n")
+            .contains("`<?>` and `<shift-?>` are the same key sequence")
+      );
+   }
+
+   #[test]
+   fn test_keymaps() {
+      let keys = |raw| parse_key_sequence(raw).unwrap();
+
+      let keymaps = Config::default().keymaps().unwrap();
+      assert_eq!(keymaps[&Mode::Home][&keys("<g><g>")], Action::SelectFirst);
+
+      let config =
+         Config::parse("keymaps:\n  Home:\n    \"<g><g>\": null\n    \"<ctrl-d>\": Quit\n")
+            .unwrap();
+      let keymaps = config.keymaps().unwrap();
+      assert!(!keymaps[&Mode::Home].contains_key(&keys("<g><g>")));
+      assert_eq!(keymaps[&Mode::Home][&keys("<ctrl-d>")], Action::Quit);
+
+      let config =
+         Config::parse("keymaps:\n  Home:\n    \"<g>\": Quit\n  Search:\n    \"<x>\": Quit\n")
+            .unwrap();
+      let error = config.keymaps().unwrap_err().to_string();
+      assert!(!error.contains("Home"));
+      assert!(error.contains("Search: `<x>` (Quit) starts with a key which is needed for typing"));
+   }
+
+   #[test]
+   fn test_format_key_sequence_round_trip() {
+      for raw in [
+         "<f1>",
+         "<ctrl-f5>",
+         "<shift-f12>",
+         "<ctrl-d>",
+         "<alt-pageup>",
+         "<space>",
+         "<g><g>",
+         "<backtab>",
+      ] {
+         let keys = parse_key_sequence(raw).unwrap();
+         let formatted = format_key_sequence(&keys);
+         assert_eq!(
+            parse_key_sequence(&formatted),
+            Ok(keys),
+            "{raw} -> {formatted}"
+         );
+      }
+      assert_eq!(
+         format_key_sequence(&parse_key_sequence("<ctrl-f5>").unwrap()),
+         "<ctrl-f5>"
+      );
+   }
+}
@@ -0,0 +1,108 @@
+use std::{io::Write, path::PathBuf};
+
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use pueue_lib::message::EditableTask;
+use serde::{Deserialize, Serialize};
+
+/// The fields of a task as they are shown in the editor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EditableFields {
+   command: String,
+   path: PathBuf,
+   label: Option<String>,
+   priority: i32,
+}
+
+/// Open the task in `$VISUAL` or `$EDITOR` and return the edited task.
+///
+/// Returns `None` if the file has been emptied to cancel editing.
+pub async fn edit_task(task: &EditableTask) -> color_eyre::Result<Option<EditableTask>> {
+   let mut file = tempfile::Builder::new()
+      .prefix(&format!("pueue-tui-task-{}-", task.id))
+      .suffix(".yml")
+      .tempfile()
+      .wrap_err("Failed to create a temporary file")?;
+   file.write_all(to_yaml(task)?.as_bytes())?;
+   file.flush()?;
+
+   let editor = std::env::var("VISUAL")
+      .or_else(|_| std::env::var("EDITOR"))
+      .unwrap_or_else(|_| "vi".to_string());
+   let mut args = shlex::split(&editor)
+      .filter(|args| !args.is_empty())
+      .ok_or_else(|| eyre!("Invalid editor command `{editor}`"))?;
+   let program = args.remove(0);
+   let status = tokio::process::Command::new(&program)
+      .args(args)
+      .arg(file.path())
+      .status()
+      .await
+      .wrap_err_with(|| format!("Failed to run `{editor}`"))?;
+   if !status.success() {
+      bail!("`{editor}` exited with {status}");
+   }
+
+   let content = std::fs::read_to_string(file.path())?;
+   from_yaml(task.id, &content)
+}
+
+fn to_yaml(task: &EditableTask) -> color_eyre::Result<String> {
+   let fields = EditableFields {
+      command: task.original_command.clone(),
+      path: task.path.clone(),
+      label: task.label.clone(),
+      priority: task.priority,
+   };
+   Ok(format!(
+      "# Editing task {}. Save and quit to apply the changes, empty the file to cancel.\n{}",
+      task.id,
+      serde_yaml::to_string(&fields)?
+   ))
+}
+
+fn from_yaml(task_id: usize, content: &str) -> color_eyre::Result<Option<EditableTask>> {
+   let is_empty = content
+      .lines()
+      .map(str::trim)
+      .all(|line| line.is_empty() || line.starts_with('#'));
+   if is_empty {
+      return Ok(None);
+   }
+
+   let fields: EditableFields =
+      serde_yaml::from_str(content).wrap_err("The edited task is invalid")?;
+   if fields.command.trim().is_empty() {
+      bail!("The command must not be empty");
+   }
+   Ok(Some(EditableTask {
+      id: task_id,
+      original_command: fields.command,
+      path: fields.path,
+      label: fields.label.filter(|label| !label.is_empty()),
+      priority: fields.priority,
+   }))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_yaml_round_trip() {
+      let task = EditableTask {
+         id: 3,
+         original_command: "sleep 60 && echo 'done: ok'".to_string(),
+         path: PathBuf::from("/tmp"),
+         label: None,
+         priority: 2,
+      };
+      let yaml = to_yaml(&task).unwrap();
+      assert_eq!(from_yaml(3, &yaml).unwrap(), Some(task));
+
+      assert_eq!(from_yaml(3, "# cancelled\n\n").unwrap(), None);
+      assert!(from_yaml(3, "command: ls\npath: /\npriority: 0\nfoo: bar\n").is_err());
+   }
+}
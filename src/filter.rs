@@ -0,0 +1,344 @@
+use std::{fmt, path::PathBuf};
+
+use chrono::{Local, TimeDelta};
+use pueue_lib::{Task, TaskResult, TaskStatus};
+
+/// A parsed filter expression such as `status:failed group:gpu since:2h`.
+///
+/// All terms have to match for a task to pass the filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+   terms: Vec<Term>,
+}
+
+/// A single `key:value` term, optionally negated with a leading `-`.
+///
+/// A comma separated value matches if any of the values matches, e.g. `status:failed,killed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+   negated: bool,
+   values: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+   Status(StatusFilter),
+   Group(String),
+   /// Case-insensitive substring of the label.
+   Label(String),
+   /// Case-insensitive substring of the command.
+   Command(String),
+   /// The task's working directory is this path or inside of it.
+   Path(PathBuf),
+   /// The task has been created within this duration.
+   Since(TimeDelta),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+   Locked,
+   Stashed,
+   Queued,
+   Running,
+   Paused,
+   Done,
+   Success,
+   Failed,
+   Killed,
+}
+
+impl StatusFilter {
+   pub const NAMES: [(&str, StatusFilter); 9] = [
+      ("locked", StatusFilter::Locked),
+      ("stashed", StatusFilter::Stashed),
+      ("queued", StatusFilter::Queued),
+      ("running", StatusFilter::Running),
+      ("paused", StatusFilter::Paused),
+      ("done", StatusFilter::Done),
+      ("success", StatusFilter::Success),
+      ("failed", StatusFilter::Failed),
+      ("killed", StatusFilter::Killed),
+   ];
+
+   fn matches(&self, status: &TaskStatus) -> bool {
+      match (self, status) {
+         (StatusFilter::Locked, TaskStatus::Locked { .. })
+         | (StatusFilter::Stashed, TaskStatus::Stashed { .. })
+         | (StatusFilter::Queued, TaskStatus::Queued { .. })
+         | (StatusFilter::Running, TaskStatus::Running { .. })
+         | (StatusFilter::Paused, TaskStatus::Paused { .. })
+         | (StatusFilter::Done, TaskStatus::Done { .. }) => true,
+         (StatusFilter::Success, TaskStatus::Done { result, .. }) => {
+            matches!(result, TaskResult::Success)
+         }
+         (StatusFilter::Failed, TaskStatus::Done { result, .. }) => {
+            !matches!(result, TaskResult::Success)
+         }
+         (StatusFilter::Killed, TaskStatus::Done { result, .. }) => {
+            matches!(result, TaskResult::Killed)
+         }
+         _ => false,
+      }
+   }
+}
+
+pub const KEYS: [&str; 6] = ["status", "group", "label", "command", "path", "since"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+   /// Char offset of the offending part of the expression.
+   pub position: usize,
+   pub kind: FilterErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterErrorKind {
+   MissingSeparator(String),
+   UnknownKey(String),
+   EmptyValue(String),
+   UnknownStatus(String),
+   InvalidDuration(String),
+}
+
+impl fmt::Display for FilterError {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let column = self.position + 1;
+      match &self.kind {
+         FilterErrorKind::MissingSeparator(term) => {
+            write!(f, "Expected `key:value` at column {column}, got `{term}`")
+         }
+         FilterErrorKind::UnknownKey(key) => write!(
+            f,
+            "Unknown filter `{key}` at column {column}, expected one of: {}",
+            KEYS.join(", ")
+         ),
+         FilterErrorKind::EmptyValue(key) => {
+            write!(f, "Missing value for `{key}` at column {column}")
+         }
+         FilterErrorKind::UnknownStatus(status) => write!(
+            f,
+            "Unknown status `{status}` at column {column}, expected one of: {}",
+            StatusFilter::NAMES.map(|(name, _)| name).join(", ")
+         ),
+         FilterErrorKind::InvalidDuration(duration) => write!(
+            f,
+            "Invalid duration `{duration}` at column {column}, expected e.g. `30m`, `2h` or `1d12h`"
+         ),
+      }
+   }
+}
+
+impl std::error::Error for FilterError {}
+
+impl Filter {
+   pub fn parse(raw: &str) -> Result<Self, FilterError> {
+      let terms = tokenize(raw)
+         .into_iter()
+         .map(|(position, token)| Term::parse(position, token))
+         .collect::<Result<_, _>>()?;
+      Ok(Self { terms })
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.terms.is_empty()
+   }
+
+   pub fn matches(&self, task: &Task) -> bool {
+      self.terms.iter().all(|term| term.matches(task))
+   }
+}
+
+impl Term {
+   fn parse(position: usize, token: &str) -> Result<Self, FilterError> {
+      let (negated, position, token) = match token.strip_prefix('-') {
+         Some(rest) => (true, position + 1, rest),
+         None => (false, position, token),
+      };
+      let error = |position, kind| FilterError { position, kind };
+
+      let Some((key, value)) = token.split_once(':') else {
+         return Err(error(
+            position,
+            FilterErrorKind::MissingSeparator(token.to_string()),
+         ));
+      };
+      if !KEYS.contains(&key) {
+         return Err(error(
+            position,
+            FilterErrorKind::UnknownKey(key.to_string()),
+         ));
+      }
+
+      let mut values = Vec::new();
+      let mut value_position = position + key.chars().count() + 1;
+      for value in value.split(',') {
+         if value.is_empty() {
+            return Err(error(
+               value_position,
+               FilterErrorKind::EmptyValue(key.to_string()),
+            ));
+         }
+         let predicate = match key {
+            "status" => Predicate::Status(
+               StatusFilter::NAMES
+                  .iter()
+                  .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                  .map(|(_, status)| *status)
+                  .ok_or_else(|| {
+                     error(
+                        value_position,
+                        FilterErrorKind::UnknownStatus(value.to_string()),
+                     )
+                  })?,
+            ),
+            "group" => Predicate::Group(value.to_string()),
+            "label" => Predicate::Label(value.to_lowercase()),
+            "command" => Predicate::Command(value.to_lowercase()),
+            "path" => Predicate::Path(expand_home(value)),
+            "since" => Predicate::Since(parse_duration(value).ok_or_else(|| {
+               error(
+                  value_position,
+                  FilterErrorKind::InvalidDuration(value.to_string()),
+               )
+            })?),
+            _ => unreachable!(),
+         };
+         values.push(predicate);
+         value_position += value.chars().count() + 1;
+      }
+
+      Ok(Self { negated, values })
+   }
+
+   fn matches(&self, task: &Task) -> bool {
+      self.values.iter().any(|predicate| predicate.matches(task)) != self.negated
+   }
+}
+
+impl Predicate {
+   fn matches(&self, task: &Task) -> bool {
+      match self {
+         Predicate::Status(status) => status.matches(&task.status),
+         Predicate::Group(group) => &task.group == group,
+         Predicate::Label(label) => task
+            .label
+            .as_ref()
+            .is_some_and(|l| l.to_lowercase().contains(label)),
+         Predicate::Command(command) => task.command.to_lowercase().contains(command),
+         Predicate::Path(path) => task.path.starts_with(path),
+         Predicate::Since(duration) => Local::now() - task.created_at <= *duration,
+      }
+   }
+}
+
+/// Split the expression at whitespace, keeping the char offset of each token.
+fn tokenize(raw: &str) -> Vec<(usize, &str)> {
+   let mut tokens = Vec::new();
+   let mut start = None;
+   for (position, (i, c)) in raw.char_indices().chain([(raw.len(), ' ')]).enumerate() {
+      match (start, c.is_whitespace()) {
+         (None, false) => start = Some((position, i)),
+         (Some((token_position, token_start)), true) => {
+            tokens.push((token_position, &raw[token_start..i]));
+            start = None;
+         }
+         _ => {}
+      }
+   }
+   tokens
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+   match (path.strip_prefix('~'), dirs::home_dir()) {
+      (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+      _ => PathBuf::from(path),
+   }
+}
+
+/// Parse durations like `90s`, `30m`, `2h` or `1d12h`.
+pub fn parse_duration(raw: &str) -> Option<TimeDelta> {
+   let mut total = TimeDelta::zero();
+   let mut number = String::new();
+   for c in raw.chars() {
+      if c.is_ascii_digit() {
+         number.push(c);
+         continue;
+      }
+      let amount: i64 = number.parse().ok()?;
+      number.clear();
+      total += match c {
+         's' => TimeDelta::try_seconds(amount)?,
+         'm' => TimeDelta::try_minutes(amount)?,
+         'h' => TimeDelta::try_hours(amount)?,
+         'd' => TimeDelta::try_days(amount)?,
+         'w' => TimeDelta::try_weeks(amount)?,
+         _ => return None,
+      };
+   }
+   number.is_empty().then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_parse_filter() {
+      let filter = Filter::parse("status:failed,killed  -group:gpu since:1d12h").unwrap();
+      assert_eq!(
+         filter.terms,
+         vec![
+            Term {
+               negated: false,
+               values: vec![
+                  Predicate::Status(StatusFilter::Failed),
+                  Predicate::Status(StatusFilter::Killed)
+               ],
+            },
+            Term {
+               negated: true,
+               values: vec![Predicate::Group("gpu".to_string())],
+            },
+            Term {
+               negated: false,
+               values: vec![Predicate::Since(TimeDelta::hours(36))],
+            },
+         ]
+      );
+      assert!(Filter::parse("  ").unwrap().is_empty());
+   }
+
+   #[test]
+   fn test_parse_filter_errors() {
+      let error = |raw| Filter::parse(raw).unwrap_err();
+
+      assert_eq!(
+         error("group:gpu foo:bar"),
+         FilterError {
+            position: 10,
+            kind: FilterErrorKind::UnknownKey("foo".to_string()),
+         }
+      );
+      assert_eq!(
+         error("status:failed,nope"),
+         FilterError {
+            position: 14,
+            kind: FilterErrorKind::UnknownStatus("nope".to_string()),
+         }
+      );
+      assert_eq!(
+         error("since:2x").kind,
+         FilterErrorKind::InvalidDuration("2x".to_string())
+      );
+      assert_eq!(
+         error("gpu").kind,
+         FilterErrorKind::MissingSeparator("gpu".to_string())
+      );
+      assert_eq!(
+         error("label:").kind,
+         FilterErrorKind::EmptyValue("label".to_string())
+      );
+   }
+}
@@ -0,0 +1,124 @@
+/// The result of matching a pattern against a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+   pub score: i64,
+   /// Char indices of the matched characters in the text.
+   pub positions: Vec<usize>,
+}
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_FIRST_CHAR: i64 = 8;
+const PENALTY_GAP: i64 = 1;
+const MAX_LEADING_PENALTY: i64 = 8;
+
+/// Match `pattern` as a subsequence of `text`.
+///
+/// Matching is case-insensitive unless the pattern contains an uppercase character.
+/// Every possible start position is tried and the best scoring one is returned, so
+/// `"sl"` prefers the `sl` of `sleep` over the `s` of `ls` followed by a later `l`.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+   if pattern.is_empty() {
+      return Some(FuzzyMatch {
+         score: 0,
+         positions: vec![],
+      });
+   }
+
+   let case_sensitive = pattern.chars().any(char::is_uppercase);
+   let normalize = |c: char| {
+      if case_sensitive {
+         c
+      } else {
+         c.to_ascii_lowercase()
+      }
+   };
+   let pattern: Vec<char> = pattern.chars().map(normalize).collect();
+   let text: Vec<char> = text.chars().collect();
+
+   (0..text.len())
+      .filter(|&start| normalize(text[start]) == pattern[0])
+      .filter_map(|start| match_from(&pattern, &text, start, normalize))
+      .max_by(|a, b| a.score.cmp(&b.score).then(b.positions.cmp(&a.positions)))
+}
+
+fn match_from(
+   pattern: &[char],
+   text: &[char],
+   start: usize,
+   normalize: impl Fn(char) -> char,
+) -> Option<FuzzyMatch> {
+   let mut positions = Vec::with_capacity(pattern.len());
+   let mut pattern_chars = pattern.iter().peekable();
+   for (i, c) in text.iter().enumerate().skip(start) {
+      match pattern_chars.peek() {
+         Some(&&p) if p == normalize(*c) => {
+            positions.push(i);
+            pattern_chars.next();
+         }
+         Some(_) => {}
+         None => break,
+      }
+   }
+   if pattern_chars.peek().is_some() {
+      return None;
+   }
+
+   let mut score = -(start as i64 * PENALTY_GAP).min(MAX_LEADING_PENALTY);
+   for (n, &i) in positions.iter().enumerate() {
+      score += SCORE_MATCH;
+      if i == 0 {
+         score += BONUS_FIRST_CHAR;
+      }
+      if i == 0 || !text[i - 1].is_alphanumeric() {
+         score += BONUS_BOUNDARY;
+      }
+      if n > 0 {
+         let gap = i - positions[n - 1] - 1;
+         if gap == 0 {
+            score += BONUS_CONSECUTIVE;
+         } else {
+            score -= gap as i64 * PENALTY_GAP;
+         }
+      }
+   }
+
+   Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_fuzzy_match_positions() {
+      let m = fuzzy_match("slp", "sleep 100").unwrap();
+      assert_eq!(m.positions, vec![0, 1, 4]);
+
+      assert_eq!(fuzzy_match("xyz", "sleep 100"), None);
+      assert_eq!(
+         fuzzy_match("", "sleep").unwrap().positions,
+         Vec::<usize>::new()
+      );
+   }
+
+   #[test]
+   fn test_fuzzy_match_smart_case() {
+      assert!(fuzzy_match("SLEEP", "sleep").is_none());
+      assert!(fuzzy_match("sleep", "SLEEP").is_some());
+   }
+
+   #[test]
+   fn test_fuzzy_match_prefers_consecutive_and_boundaries() {
+      let scattered = fuzzy_match("ls", "echo less").unwrap();
+      let prefix = fuzzy_match("ls", "ls -al").unwrap();
+      assert!(prefix.score > scattered.score);
+
+      // The best start position is picked, not the first one.
+      let m = fuzzy_match("sl", "ls; sleep").unwrap();
+      assert_eq!(m.positions, vec![4, 5]);
+   }
+}
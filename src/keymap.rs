@@ -0,0 +1,420 @@
+use std::{
+   collections::HashMap,
+   time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+   action::Action,
+   config::{Keymap, format_key_sequence, key_event_to_string},
+};
+
+/// The largest count, so a mistyped count doesn't flood the action channel.
+const MAX_COUNT: usize = 9999;
+
+/// The key bindings of a mode, stored as a trie so that sequences can be matched key by key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KeyTrie {
+   action: Option<Action>,
+   children: HashMap<KeyEvent, KeyTrie>,
+}
+
+impl KeyTrie {
+   pub fn new(keymap: &Keymap) -> Self {
+      let mut root = Self::default();
+      for (keys, action) in keymap {
+         let node = keys.iter().fold(&mut root, |node, key| {
+            node.children.entry(*key).or_default()
+         });
+         node.action = Some(action.clone());
+      }
+      root
+   }
+
+   /// The node reached by typing `keys`, if any binding starts with them.
+   pub fn get(&self, keys: &[KeyEvent]) -> Option<&KeyTrie> {
+      keys
+         .iter()
+         .try_fold(self, |node, key| node.children.get(key))
+   }
+
+   /// The action bound to the keys leading to this node.
+   pub fn action(&self) -> Option<&Action> {
+      self.action.as_ref()
+   }
+
+   /// The keys which continue the sequence leading to this node.
+   pub fn children(&self) -> impl Iterator<Item = (&KeyEvent, &KeyTrie)> {
+      self.children.iter()
+   }
+
+   fn is_leaf(&self) -> bool {
+      self.children.is_empty()
+   }
+}
+
+/// Matches typed keys against a [`KeyTrie`].
+///
+/// If a binding is a prefix of a longer one, the matcher waits for the next key. The shorter
+/// binding runs once the timeout has passed or a key which doesn't continue the sequence is
+/// typed. Digits typed before a binding are a count, like `5j` in vim.
+#[derive(Debug, Clone)]
+pub struct KeySequenceMatcher {
+   timeout: Duration,
+   pending: Vec<KeyEvent>,
+   count: Option<usize>,
+   deadline: Option<Instant>,
+}
+
+impl KeySequenceMatcher {
+   pub fn new(timeout: Duration) -> Self {
+      Self {
+         timeout,
+         pending: Vec::new(),
+         count: None,
+         deadline: None,
+      }
+   }
+
+   /// The keys typed so far of an unfinished sequence.
+   pub fn pending(&self) -> &[KeyEvent] {
+      &self.pending
+   }
+
+   /// When the pending keys time out, see [`KeySequenceMatcher::check_timeout`].
+   pub fn deadline(&self) -> Option<Instant> {
+      self.deadline
+   }
+
+   /// The count typed before the pending keys.
+   pub fn count(&self) -> Option<usize> {
+      self.count
+   }
+
+   pub fn reset(&mut self) {
+      self.pending.clear();
+      self.count = None;
+      self.deadline = None;
+   }
+
+   /// Feed a key and return the matched actions with their counts.
+   ///
+   /// Counts are only read if `counts` is set, modes with a text input need the digits.
+   pub fn push(
+      &mut self,
+      trie: &KeyTrie,
+      key: KeyEvent,
+      counts: bool,
+      now: Instant,
+   ) -> Vec<(Action, usize)> {
+      let key = normalize(key);
+      let mut actions = Vec::new();
+      // Once a count has been started, digits continue it even if they are bound themselves.
+      if self.pending.is_empty()
+         && counts
+         && (self.count.is_some() || trie.get(&[key]).is_none())
+         && let Some(digit) = count_digit(key, self.count.is_some())
+      {
+         self.count = Some((self.count.unwrap_or(0) * 10 + digit).min(MAX_COUNT));
+         return actions;
+      }
+
+      let mut keys = self.pending.clone();
+      keys.push(key);
+      match trie.get(&keys) {
+         Some(node) if node.is_leaf() => {
+            actions.extend(
+               node
+                  .action()
+                  .cloned()
+                  .map(|action| (action, self.take_count())),
+            );
+            self.pending.clear();
+            self.deadline = None;
+         }
+         Some(_) => {
+            self.pending = keys;
+            self.deadline = Some(now + self.timeout);
+         }
+         // The key doesn't continue the pending sequence, so run the binding typed so far and
+         // start a new sequence with the key.
+         None if !self.pending.is_empty() => {
+            let pending = std::mem::take(&mut self.pending);
+            self.deadline = None;
+            match trie.get(&pending).and_then(KeyTrie::action) {
+               Some(action) => actions.push((action.clone(), self.take_count())),
+               None => self.count = None,
+            }
+            actions.extend(self.push(trie, key, counts, now));
+         }
+         None => self.count = None,
+      }
+      actions
+   }
+
+   /// Resolve a pending sequence whose timeout has passed.
+   pub fn check_timeout(&mut self, trie: &KeyTrie, now: Instant) -> Option<(Action, usize)> {
+      if self.deadline.is_none_or(|deadline| now < deadline) {
+         return None;
+      }
+      let action = trie.get(&self.pending).and_then(KeyTrie::action).cloned();
+      let count = self.take_count();
+      self.reset();
+      action.map(|action| (action, count))
+   }
+
+   fn take_count(&mut self) -> usize {
+      self.count.take().unwrap_or(1)
+   }
+}
+
+/// Drop SHIFT from characters without case, terminals differ in reporting it for e.g. `?`.
+///
+/// Parsed key sequences are normalized already, typed keys are normalized before matching.
+pub fn normalize(mut key: KeyEvent) -> KeyEvent {
+   if let KeyCode::Char(c) = key.code
+      && !c.is_alphabetic()
+   {
+      key.modifiers.remove(KeyModifiers::SHIFT);
+   }
+   key
+}
+
+/// Describe the count and keys typed so far, e.g. `5<g>`.
+pub fn describe_pending(count: Option<usize>, keys: &[KeyEvent]) -> String {
+   format!(
+      "{}{}",
+      count.map(|count| count.to_string()).unwrap_or_default(),
+      format_key_sequence(keys)
+   )
+}
+
+/// Format keys for the help and the key hints, e.g. `gg` or `ctrl-d`.
+pub fn display_keys(keys: &[KeyEvent]) -> String {
+   let keys: Vec<String> = keys
+      .iter()
+      .map(|key| {
+         // Uppercase letters and backtab imply SHIFT already.
+         let mut key = normalize(*key);
+         if key.code == KeyCode::BackTab || matches!(key.code, KeyCode::Char(c) if c.is_uppercase())
+         {
+            key.modifiers.remove(KeyModifiers::SHIFT);
+         }
+         key_event_to_string(&key)
+      })
+      .collect();
+   if keys.iter().all(|key| key.chars().count() == 1) {
+      keys.concat()
+   } else {
+      keys.join(" ")
+   }
+}
+
+/// The keys bound to `action`, the shortest first.
+pub fn bound_keys(keymap: &Keymap, action: &Action) -> Vec<String> {
+   let mut keys: Vec<_> = keymap
+      .iter()
+      .filter(|(_, bound)| *bound == action)
+      .map(|(keys, _)| (keys.len(), display_keys(keys)))
+      .collect();
+   keys.sort_by(|(a_len, a), (b_len, b)| {
+      (a_len, a.chars().count(), a).cmp(&(b_len, b.chars().count(), b))
+   });
+   keys.into_iter().map(|(_, keys)| keys).collect()
+}
+
+/// Hints like `Quit : q  Group : tab/backtab` with the shortest key of each action.
+///
+/// Hints whose actions aren't bound at all are left out.
+pub fn key_hints(keymap: &Keymap, hints: &[(&str, &[Action])]) -> String {
+   hints
+      .iter()
+      .filter_map(|(label, actions)| {
+         let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| bound_keys(keymap, action).into_iter().next())
+            .collect();
+         (!keys.is_empty()).then(|| format!("{label} : {}", keys.join("/")))
+      })
+      .collect::<Vec<_>>()
+      .join("  ")
+}
+
+/// The digit of a count typed with `key`. A leading zero isn't part of a count.
+fn count_digit(key: KeyEvent, continued: bool) -> Option<usize> {
+   match key.code {
+      KeyCode::Char(c) if key.modifiers == KeyModifiers::NONE => c
+         .to_digit(10)
+         .filter(|&digit| continued || digit != 0)
+         .map(|digit| digit as usize),
+      _ => None,
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   use crate::{action::TaskAction, config::parse_key_sequence};
+
+   fn trie(bindings: &[(&str, Action)]) -> KeyTrie {
+      KeyTrie::new(
+         &bindings
+            .iter()
+            .map(|(raw, action)| (parse_key_sequence(raw).unwrap(), action.clone()))
+            .collect(),
+      )
+   }
+
+   fn type_keys(
+      matcher: &mut KeySequenceMatcher,
+      trie: &KeyTrie,
+      raw: &str,
+      now: Instant,
+   ) -> Vec<(Action, usize)> {
+      parse_key_sequence(raw)
+         .unwrap()
+         .into_iter()
+         .flat_map(|key| matcher.push(trie, key, true, now))
+         .collect()
+   }
+
+   #[test]
+   fn test_match_sequences() {
+      let trie = trie(&[
+         ("<j>", Action::SelectNext),
+         ("<g><g>", Action::SelectFirst),
+         ("<d>", Action::ClearMarks),
+         ("<d><d>", Action::Quit),
+      ]);
+      let timeout = Duration::from_secs(1);
+      let mut matcher = KeySequenceMatcher::new(timeout);
+      let now = Instant::now();
+
+      assert_eq!(type_keys(&mut matcher, &trie, "<g>", now), vec![]);
+      assert_eq!(matcher.check_timeout(&trie, now + timeout / 2), None);
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<g>", now + timeout / 2),
+         vec![(Action::SelectFirst, 1)]
+      );
+
+      // An unfinished sequence without a binding is dropped.
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<g><j>", now),
+         vec![(Action::SelectNext, 1)]
+      );
+      assert_eq!(type_keys(&mut matcher, &trie, "<g>", now), vec![]);
+      assert_eq!(matcher.check_timeout(&trie, now + timeout), None);
+      assert!(matcher.pending().is_empty() && matcher.count().is_none());
+
+      // A binding which is a prefix of another one runs on timeout or with the next key.
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<d><d>", now),
+         vec![(Action::Quit, 1)]
+      );
+      assert_eq!(type_keys(&mut matcher, &trie, "<d>", now), vec![]);
+      assert_eq!(
+         matcher.check_timeout(&trie, now + timeout),
+         Some((Action::ClearMarks, 1))
+      );
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<d><j>", now),
+         vec![(Action::ClearMarks, 1), (Action::SelectNext, 1)]
+      );
+   }
+
+   #[test]
+   fn test_key_hints() {
+      let keymap = [
+         ("<q>", Action::Quit),
+         ("<ctrl-d>", Action::Quit),
+         ("<tab>", Action::NextGroup),
+         ("<backtab>", Action::PrevGroup),
+         (
+            "<shift-r>",
+            Action::ApplyToSelection(TaskAction::RestartAsNew),
+         ),
+         ("<g><g>", Action::SelectFirst),
+         ("<ctrl-w><l>", Action::SelectLast),
+         ("<?>", Action::OpenHelp),
+      ]
+      .into_iter()
+      .map(|(raw, action)| (parse_key_sequence(raw).unwrap(), action))
+      .collect();
+
+      assert_eq!(bound_keys(&keymap, &Action::Quit), vec!["q", "ctrl-d"]);
+      assert_eq!(bound_keys(&keymap, &Action::SelectLast), vec!["ctrl-w l"]);
+      assert_eq!(
+         bound_keys(&keymap, &Action::ApplyToSelection(TaskAction::RestartAsNew)),
+         vec!["R"]
+      );
+
+      let trie = KeyTrie::new(&keymap);
+      let mut matcher = KeySequenceMatcher::new(Duration::from_secs(1));
+      let question_mark = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT);
+      assert_eq!(
+         matcher.push(&trie, question_mark, true, Instant::now()),
+         vec![(Action::OpenHelp, 1)]
+      );
+      assert_eq!(
+         key_hints(
+            &keymap,
+            &[
+               ("Quit", &[Action::Quit]),
+               ("Help", &[Action::CloseHelp]),
+               ("Group", &[Action::NextGroup, Action::PrevGroup]),
+               ("Top", &[Action::SelectFirst]),
+            ]
+         ),
+         "Quit : q  Group : tab/backtab  Top : gg"
+      );
+   }
+
+   #[test]
+   fn test_match_counts() {
+      let trie = trie(&[
+         ("<j>", Action::SelectNext),
+         ("<0>", Action::SelectFirst),
+         ("<g><g>", Action::SelectFirst),
+      ]);
+      let mut matcher = KeySequenceMatcher::new(Duration::from_secs(1));
+      let now = Instant::now();
+
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<1><2><j>", now),
+         vec![(Action::SelectNext, 12)]
+      );
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<j>", now),
+         vec![(Action::SelectNext, 1)]
+      );
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<1><0><j>", now),
+         vec![(Action::SelectNext, 10)]
+      );
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<0>", now),
+         vec![(Action::SelectFirst, 1)]
+      );
+      assert_eq!(type_keys(&mut matcher, &trie, "<3><g>", now), vec![]);
+      assert_eq!(describe_pending(matcher.count(), matcher.pending()), "3<g>");
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<g>", now),
+         vec![(Action::SelectFirst, 3)]
+      );
+
+      // An unbound key discards the count.
+      assert_eq!(
+         type_keys(&mut matcher, &trie, "<4><x><j>", now),
+         vec![(Action::SelectNext, 1)]
+      );
+
+      let mut matcher = KeySequenceMatcher::new(Duration::from_secs(1));
+      let key = parse_key_sequence("<5>").unwrap()[0];
+      assert_eq!(matcher.push(&trie, key, false, now), vec![]);
+      assert!(matcher.pending().is_empty() && matcher.count().is_none());
+   }
+}
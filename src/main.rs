@@ -5,7 +5,15 @@ mod action;
 mod app;
 mod cli;
 mod client;
+mod command;
 mod components;
+mod config;
+mod editor;
+mod filter;
+mod fuzzy;
+mod keymap;
+mod poller;
+mod state_diff;
 mod tui;
 mod widgets;
 
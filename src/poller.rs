@@ -0,0 +1,193 @@
+use std::time::{Duration, Instant};
+
+use pueue_lib::State;
+use tokio::{
+   sync::mpsc::{UnboundedReceiver, UnboundedSender},
+   time::sleep_until,
+};
+
+use crate::{
+   action::Action,
+   client::{Client, ClientError, is_compatible_version},
+   state_diff::StateDiff,
+};
+
+/// The delay before the first attempt to reconnect to the daemon, doubled after each failure.
+const RECONNECT_DELAY_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_DELAY_MAX: Duration = Duration::from_secs(30);
+/// How long the status is fetched at the active rate after the user pressed a key.
+const ACTIVITY_DURATION: Duration = Duration::from_secs(10);
+
+/// What the poller is told about the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollHint {
+   /// A request has been sent to the daemon, so its effect should be fetched right away.
+   Refresh,
+   /// The user pressed a key.
+   Activity,
+   /// The terminal gained or lost the focus.
+   Focus(bool),
+}
+
+/// How long to wait between fetching the status, depending on what is going on.
+#[derive(Debug, Clone)]
+struct PollRate {
+   active: Duration,
+   idle: Duration,
+   focused: bool,
+   last_activity: Option<Instant>,
+}
+
+impl PollRate {
+   /// Fast while tasks run or the user is busy, slow while nothing happens or nobody looks.
+   fn interval(&self, running: bool, now: Instant) -> Duration {
+      let busy = self
+         .last_activity
+         .is_some_and(|activity| now.duration_since(activity) < ACTIVITY_DURATION);
+      if self.focused && (running || busy) {
+         self.active
+      } else {
+         self.idle
+      }
+   }
+}
+
+/// Fetches the state of the daemon and sends only what changed.
+pub struct StatusPoller {
+   client: Client,
+   action_tx: UnboundedSender<Action>,
+   rate: PollRate,
+   /// The last state sent to the components.
+   state: State,
+   daemon_version: Option<String>,
+   /// The delay before the next reconnect, or `None` while connected.
+   backoff: Option<Duration>,
+}
+
+impl StatusPoller {
+   pub fn new(
+      client: Client,
+      action_tx: UnboundedSender<Action>,
+      active: Duration,
+      idle: Duration,
+   ) -> Self {
+      Self {
+         client,
+         action_tx,
+         rate: PollRate {
+            active,
+            idle,
+            focused: true,
+            last_activity: None,
+         },
+         state: State::new(),
+         daemon_version: None,
+         backoff: None,
+      }
+   }
+
+   /// Poll until the sender of the hints is dropped.
+   pub async fn run(mut self, mut hints: UnboundedReceiver<PollHint>) {
+      loop {
+         let polled = Instant::now();
+         let retry_in = self.poll().await;
+         // Hints can make the wait shorter or longer, but only a refresh skips it.
+         loop {
+            let running = self.state.tasks.values().any(|task| task.is_running());
+            let delay = retry_in.unwrap_or_else(|| self.rate.interval(running, Instant::now()));
+            tokio::select! {
+               _ = sleep_until((polled + delay).into()) => break,
+               hint = hints.recv() => match hint {
+                  Some(PollHint::Refresh) => {
+                     self.rate.last_activity = Some(Instant::now());
+                     break;
+                  }
+                  Some(PollHint::Activity) => self.rate.last_activity = Some(Instant::now()),
+                  Some(PollHint::Focus(focused)) => self.rate.focused = focused,
+                  None => return,
+               },
+            }
+         }
+      }
+   }
+
+   /// Fetch the status once and return the delay before reconnecting if the daemon is gone.
+   async fn poll(&mut self) -> Option<Duration> {
+      match self.client.status().await {
+         Ok(state) => {
+            if self.backoff.take().is_some() {
+               let _ = self.action_tx.send(Action::Connected);
+            }
+            let diff = StateDiff::new(&self.state, &state);
+            if !diff.is_empty() {
+               let _ = self.action_tx.send(Action::StateChanged(Box::new(diff)));
+            }
+            self.state = state;
+            self.check_version().await;
+            None
+         }
+         Err(e @ (ClientError::Connection(_) | ClientError::Authentication(_))) => {
+            let retry_in = self.backoff.map_or(RECONNECT_DELAY_MIN, |delay| {
+               (delay * 2).min(RECONNECT_DELAY_MAX)
+            });
+            self.backoff = Some(retry_in);
+            let _ = self.action_tx.send(Action::Disconnected {
+               error: e.to_string(),
+               retry_in,
+            });
+            Some(retry_in)
+         }
+         Err(e) => {
+            let _ = self
+               .action_tx
+               .send(Action::Error(format!("Failed to fetch status: {e}")));
+            // The daemon answered, so its version tells why it may not be understood.
+            self.check_version().await;
+            None
+         }
+      }
+   }
+
+   /// Report the version of the daemon, which may have been replaced while disconnected.
+   async fn check_version(&mut self) {
+      let Some(version) = self.client.daemon_version().await else {
+         return;
+      };
+      if self.daemon_version.as_ref() != Some(&version) {
+         let _ = self.action_tx.send(Action::DaemonVersion {
+            compatible: is_compatible_version(&version),
+            version: version.clone(),
+         });
+         self.daemon_version = Some(version);
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use pretty_assertions::assert_eq;
+
+   #[test]
+   fn test_interval() {
+      let active = Duration::from_secs(1);
+      let idle = Duration::from_secs(5);
+      let now = Instant::now();
+      let mut rate = PollRate {
+         active,
+         idle,
+         focused: true,
+         last_activity: None,
+      };
+      assert_eq!(rate.interval(false, now), idle);
+      assert_eq!(rate.interval(true, now), active);
+
+      rate.last_activity = Some(now);
+      assert_eq!(rate.interval(false, now + Duration::from_secs(3)), active);
+      assert_eq!(rate.interval(false, now + ACTIVITY_DURATION), idle);
+
+      rate.focused = false;
+      assert_eq!(rate.interval(true, now), idle);
+   }
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use pueue_lib::{Group, State, Task};
+use serde::{Deserialize, Serialize};
+
+/// The changes which turn one state of the daemon into the next one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDiff {
+   /// Tasks which didn't exist before.
+   pub added: Vec<Task>,
+   /// The new version of tasks whose status or any other field changed.
+   pub changed: Vec<Task>,
+   pub removed: Vec<usize>,
+   /// All groups, if any of them has been added, removed or changed.
+   pub groups: Option<BTreeMap<String, Group>>,
+}
+
+impl StateDiff {
+   pub fn new(old: &State, new: &State) -> Self {
+      let mut diff = Self::default();
+      for (id, task) in &new.tasks {
+         match old.tasks.get(id) {
+            None => diff.added.push(task.clone()),
+            Some(old_task) if old_task != task => diff.changed.push(task.clone()),
+            Some(_) => {}
+         }
+      }
+      diff.removed = old
+         .tasks
+         .keys()
+         .filter(|id| !new.tasks.contains_key(id))
+         .copied()
+         .collect();
+      if old.groups != new.groups {
+         diff.groups = Some(new.groups.clone());
+      }
+      diff
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.added.is_empty()
+         && self.changed.is_empty()
+         && self.removed.is_empty()
+         && self.groups.is_none()
+   }
+
+   /// The added and changed tasks.
+   pub fn updated(&self) -> impl Iterator<Item = &Task> {
+      self.added.iter().chain(&self.changed)
+   }
+
+   pub fn apply(&self, state: &mut State) {
+      for id in &self.removed {
+         state.tasks.remove(id);
+      }
+      for task in self.updated() {
+         state.tasks.insert(task.id, task.clone());
+      }
+      if let Some(groups) = &self.groups {
+         state.groups = groups.clone();
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use std::path::PathBuf;
+
+   use chrono::Local;
+   use pretty_assertions::assert_eq;
+   use pueue_lib::{GroupStatus, TaskStatus};
+
+   fn task(id: usize, status: TaskStatus) -> Task {
+      let mut task = Task::new(
+         format!("sleep {id}"),
+         PathBuf::from("/"),
+         Default::default(),
+         "default".to_string(),
+         status,
+         vec![],
+         0,
+         None,
+      );
+      task.id = id;
+      task
+   }
+
+   fn state(tasks: Vec<Task>) -> State {
+      let mut state = State::new();
+      state.groups.insert(
+         "default".to_string(),
+         Group {
+            status: GroupStatus::Running,
+            parallel_tasks: 1,
+         },
+      );
+      state.tasks = tasks.into_iter().map(|task| (task.id, task)).collect();
+      state
+   }
+
+   #[test]
+   fn test_diff_and_apply() {
+      let running = TaskStatus::Running {
+         enqueued_at: Local::now(),
+         start: Local::now(),
+      };
+      let old = state(vec![
+         task(
+            0,
+            TaskStatus::Queued {
+               enqueued_at: Local::now(),
+            },
+         ),
+         task(
+            1,
+            TaskStatus::Locked {
+               previous_status: Box::new(TaskStatus::Stashed { enqueue_at: None }),
+            },
+         ),
+         task(2, running.clone()),
+      ]);
+      let mut new = state(vec![
+         task(0, running),
+         old.tasks[&2].clone(),
+         task(3, TaskStatus::Stashed { enqueue_at: None }),
+      ]);
+      new.groups.get_mut("default").unwrap().parallel_tasks = 2;
+
+      let diff = StateDiff::new(&old, &new);
+      assert_eq!(
+         diff.added.iter().map(|task| task.id).collect::<Vec<_>>(),
+         [3]
+      );
+      assert_eq!(
+         diff.changed.iter().map(|task| task.id).collect::<Vec<_>>(),
+         [0]
+      );
+      assert_eq!(diff.removed, [1]);
+      assert_eq!(diff.groups.as_ref(), Some(&new.groups));
+
+      let mut applied = old.clone();
+      diff.apply(&mut applied);
+      assert_eq!(applied, new);
+      assert!(StateDiff::new(&new, &applied).is_empty());
+   }
+}
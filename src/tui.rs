@@ -7,8 +7,9 @@ use std::{
 use crossterm::{
    cursor,
    event::{
-      DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
-      Event as CrosstermEvent, EventStream, KeyEvent, KeyEventKind, MouseEvent,
+      DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+      EnableFocusChange, EnableMouseCapture, Event as CrosstermEvent, EventStream, KeyEvent,
+      KeyEventKind, MouseEvent,
    },
    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -29,7 +30,6 @@ pub enum Event {
    Error,
    Closed,
    Tick,
-   Render,
    FocusGained,
    FocusLost,
    Paste(String),
@@ -40,7 +40,6 @@ pub enum Event {
 
 #[derive(Clone)]
 pub struct TuiConfig {
-   pub frame_rate: f64,
    pub tick_rate: f64,
    pub mouse: bool,
    pub paste: bool,
@@ -52,7 +51,6 @@ pub struct Tui {
    pub cancellation_token: CancellationToken,
    pub event_rx: UnboundedReceiver<Event>,
    pub event_tx: UnboundedSender<Event>,
-   pub frame_rate: f64,
    pub tick_rate: f64,
    pub mouse: bool,
    pub paste: bool,
@@ -67,7 +65,6 @@ impl Tui {
          cancellation_token: CancellationToken::new(),
          event_rx,
          event_tx,
-         frame_rate: 60.0,
          tick_rate: 4.0,
          mouse: false,
          paste: false,
@@ -79,11 +76,6 @@ impl Tui {
       self
    }
 
-   pub fn frame_rate(mut self, frame_rate: f64) -> Self {
-      self.frame_rate = frame_rate;
-      self
-   }
-
    pub fn mouse(mut self, mouse: bool) -> Self {
       self.mouse = mouse;
       self
@@ -101,7 +93,6 @@ impl Tui {
          self.event_tx.clone(),
          self.cancellation_token.clone(),
          self.tick_rate,
-         self.frame_rate,
       );
       self.task = tokio::spawn(async {
          event_loop.await;
@@ -112,11 +103,9 @@ impl Tui {
       event_tx: UnboundedSender<Event>,
       cancellation_token: CancellationToken,
       tick_rate: f64,
-      frame_rate: f64,
    ) {
       let mut event_stream = EventStream::new();
       let mut tick_interval = interval(Duration::from_secs_f64(1.0 / tick_rate));
-      let mut render_interval = interval(Duration::from_secs_f64(1.0 / frame_rate));
 
       // if this fails, then it's likely a bug in the calling code
       event_tx
@@ -128,7 +117,6 @@ impl Tui {
                  break;
              }
              _ = tick_interval.tick() => Event::Tick,
-             _ = render_interval.tick() => Event::Render,
              crossterm_event = event_stream.next().fuse() => match crossterm_event {
                  Some(Ok(event)) => match event {
                      CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Event::Key(key),
@@ -169,7 +157,12 @@ impl Tui {
 
    pub fn enter(&mut self) -> color_eyre::Result<()> {
       crossterm::terminal::enable_raw_mode()?;
-      crossterm::execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
+      crossterm::execute!(
+         stdout(),
+         EnterAlternateScreen,
+         cursor::Hide,
+         EnableFocusChange
+      )?;
       if self.mouse {
          crossterm::execute!(stdout(), EnableMouseCapture)?;
       }
@@ -190,7 +183,12 @@ impl Tui {
          if self.mouse {
             crossterm::execute!(stdout(), DisableMouseCapture)?;
          }
-         crossterm::execute!(stdout(), LeaveAlternateScreen, cursor::Show)?;
+         crossterm::execute!(
+            stdout(),
+            DisableFocusChange,
+            LeaveAlternateScreen,
+            cursor::Show
+         )?;
          crossterm::terminal::disable_raw_mode()?;
       }
       Ok(())
@@ -230,7 +228,6 @@ impl TryFrom<&TuiConfig> for Tui {
 
    fn try_from(config: &TuiConfig) -> Result<Self, Self::Error> {
       Ok(Tui::new()?
-         .frame_rate(config.frame_rate)
          .tick_rate(config.tick_rate)
          .mouse(config.mouse)
          .paste(config.paste))
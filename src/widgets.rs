@@ -1,2 +1,6 @@
+pub mod checkbox;
+pub mod form;
+pub mod group_tabs;
 pub mod status_bar;
 pub mod task_table;
+pub mod text_input;
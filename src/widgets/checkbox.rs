@@ -0,0 +1,50 @@
+use ratatui::{
+   buffer::Buffer,
+   layout::Rect,
+   style::{Style, Stylize},
+   text::{Line, Span},
+   widgets::Widget,
+};
+
+/// A `[x] label` toggle.
+pub struct Checkbox<'a> {
+   label: &'a str,
+   checked: bool,
+   focused: bool,
+}
+
+impl<'a> Checkbox<'a> {
+   pub fn new(label: &'a str) -> Self {
+      Self {
+         label,
+         checked: false,
+         focused: true,
+      }
+   }
+
+   pub fn checked(mut self, checked: bool) -> Self {
+      self.checked = checked;
+      self
+   }
+
+   pub fn focused(mut self, focused: bool) -> Self {
+      self.focused = focused;
+      self
+   }
+}
+
+impl Widget for Checkbox<'_> {
+   fn render(self, area: Rect, buf: &mut Buffer) {
+      let style = if self.focused {
+         Style::new()
+      } else {
+         Style::new().dim()
+      };
+      let symbol = if self.checked { "[x]" } else { "[ ]" };
+      let mut spans = vec![Span::from(symbol).bold()];
+      if !self.label.is_empty() {
+         spans.push(Span::from(format!(" {}", self.label)));
+      }
+      Line::from(spans).style(style).render(area, buf);
+   }
+}
@@ -0,0 +1,197 @@
+use std::marker::PhantomData;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+   buffer::Buffer,
+   layout::{Constraint, Layout, Position, Rect},
+   style::Style,
+   text::Line,
+   widgets::{Block, StatefulWidget, Widget},
+};
+
+use super::{
+   checkbox::Checkbox,
+   text_input::{TextInput, TextInputState},
+};
+
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+   Text(TextInputState),
+   Checkbox(bool),
+}
+
+/// A labelled field of a [`Form`], identified by `key`.
+#[derive(Debug, Clone)]
+pub struct Field<K> {
+   key: K,
+   label: String,
+   value: FieldValue,
+   placeholder: String,
+}
+
+impl<K> Field<K> {
+   pub fn text(key: K, label: &str, value: &str) -> Self {
+      Self {
+         key,
+         label: label.to_string(),
+         value: FieldValue::Text(TextInputState::new(value)),
+         placeholder: String::new(),
+      }
+   }
+
+   pub fn checkbox(key: K, label: &str, checked: bool) -> Self {
+      Self {
+         key,
+         label: label.to_string(),
+         value: FieldValue::Checkbox(checked),
+         placeholder: String::new(),
+      }
+   }
+
+   /// Text shown dimmed while a text field is empty.
+   pub fn placeholder(mut self, placeholder: &str) -> Self {
+      self.placeholder = placeholder.to_string();
+      self
+   }
+}
+
+/// The fields of a [`Form`] and which of them has the focus.
+#[derive(Debug, Clone)]
+pub struct FormState<K> {
+   fields: Vec<Field<K>>,
+   focused: usize,
+}
+
+impl<K: Copy + PartialEq> FormState<K> {
+   pub fn new(fields: Vec<Field<K>>) -> Self {
+      Self { fields, focused: 0 }
+   }
+
+   pub fn focus_next(&mut self) {
+      self.focused = (self.focused + 1) % self.fields.len();
+   }
+
+   pub fn focus_prev(&mut self) {
+      self.focused = (self.focused + self.fields.len() - 1) % self.fields.len();
+   }
+
+   /// The value of a text field, or an empty string if `key` is not a text field.
+   pub fn text(&self, key: K) -> &str {
+      match self.value(key) {
+         Some(FieldValue::Text(input)) => input.value(),
+         _ => "",
+      }
+   }
+
+   /// Whether a checkbox is checked, or `false` if `key` is not a checkbox.
+   pub fn checked(&self, key: K) -> bool {
+      matches!(self.value(key), Some(FieldValue::Checkbox(true)))
+   }
+
+   /// Edit the focused field.
+   ///
+   /// Returns whether a value changed.
+   pub fn handle_key_event(&mut self, key: KeyEvent) -> bool {
+      match &mut self.fields[self.focused].value {
+         FieldValue::Text(input) => input.handle_key_event(key),
+         FieldValue::Checkbox(checked) => {
+            if key.code == KeyCode::Char(' ') {
+               *checked = !*checked;
+               true
+            } else {
+               false
+            }
+         }
+      }
+   }
+
+   fn value(&self, key: K) -> Option<&FieldValue> {
+      self
+         .fields
+         .iter()
+         .find(|field| field.key == key)
+         .map(|field| &field.value)
+   }
+}
+
+/// A bordered list of labelled text inputs and checkboxes, one per line.
+pub struct Form<'a, K> {
+   title: &'a str,
+   key: PhantomData<K>,
+}
+
+impl<'a, K> Form<'a, K> {
+   pub fn new(title: &'a str) -> Self {
+      Self {
+         title,
+         key: PhantomData,
+      }
+   }
+
+   /// The height needed to show all fields of `state`.
+   pub fn height(state: &FormState<K>) -> u16 {
+      state.fields.len() as u16 + 2
+   }
+
+   /// Where the terminal cursor should be placed if a text field has the focus.
+   pub fn cursor_position(&self, area: Rect, state: &FormState<K>) -> Option<Position> {
+      let field = &state.fields[state.focused];
+      let FieldValue::Text(input) = &field.value else {
+         return None;
+      };
+      let (_, value_areas) = Self::areas(area, state);
+      Some(TextInput::new("").cursor_position(value_areas[state.focused], input))
+   }
+
+   fn areas(area: Rect, state: &FormState<K>) -> (Vec<Rect>, Vec<Rect>) {
+      let inner = Block::bordered().inner(area);
+      let label_width = state
+         .fields
+         .iter()
+         .map(|field| field.label.chars().count())
+         .max()
+         .unwrap_or(0) as u16
+         + 2;
+      let rows = Layout::vertical(vec![Constraint::Length(1); state.fields.len()]).split(inner);
+      rows
+         .iter()
+         .map(|row| {
+            let [label, value] =
+               Layout::horizontal([Constraint::Length(label_width), Constraint::Fill(1)])
+                  .areas(*row);
+            (label, value)
+         })
+         .unzip()
+   }
+}
+
+impl<K> StatefulWidget for Form<'_, K> {
+   type State = FormState<K>;
+
+   fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+      Block::bordered()
+         .title(format!(" {} ", self.title))
+         .render(area, buf);
+
+      let (label_areas, value_areas) = Self::areas(area, state);
+      for (i, field) in state.fields.iter_mut().enumerate() {
+         let focused = i == state.focused;
+         let label_style = if focused {
+            Style::new().bold().yellow()
+         } else {
+            Style::new()
+         };
+         Line::styled(field.label.as_str(), label_style).render(label_areas[i], buf);
+         match &mut field.value {
+            FieldValue::Text(input) => TextInput::new("")
+               .placeholder(&field.placeholder)
+               .focused(focused)
+               .render(value_areas[i], buf, input),
+            FieldValue::Checkbox(checked) => Checkbox::new("")
+               .checked(*checked)
+               .focused(focused)
+               .render(value_areas[i], buf),
+         }
+      }
+   }
+}
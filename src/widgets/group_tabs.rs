@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use pueue_lib::{Group, GroupStatus};
+use ratatui::{
+   buffer::Buffer,
+   layout::Rect,
+   style::{Style, Stylize},
+   text::{Line, Span},
+   widgets::{Tabs, Widget},
+};
+
+pub struct GroupTabs<'a> {
+   groups: &'a BTreeMap<String, Group>,
+   selected: Option<&'a str>,
+}
+
+impl<'a> GroupTabs<'a> {
+   /// `selected` is the name of the selected group, or `None` if the "All" tab is selected.
+   pub fn new(groups: &'a BTreeMap<String, Group>, selected: Option<&'a str>) -> Self {
+      Self { groups, selected }
+   }
+
+   fn group_to_title(name: &'a str, group: &Group) -> Line<'a> {
+      let (status, style) = match group.status {
+         GroupStatus::Running => ("running", Style::new().green()),
+         GroupStatus::Paused => ("paused", Style::new().yellow()),
+         GroupStatus::Reset => ("resetting", Style::new().red()),
+      };
+      Line::from(vec![
+         Span::from(name).bold(),
+         Span::from(format!(" ({} parallel): ", group.parallel_tasks)),
+         Span::from(status).style(style),
+      ])
+   }
+}
+
+impl Widget for GroupTabs<'_> {
+   fn render(self, area: Rect, buf: &mut Buffer) {
+      let selected = match self.selected {
+         Some(name) => self
+            .groups
+            .keys()
+            .position(|g| g == name)
+            .map_or(0, |i| i + 1),
+         None => 0,
+      };
+      let titles = std::iter::once(Line::from("All").bold()).chain(
+         self
+            .groups
+            .iter()
+            .map(|(name, group)| Self::group_to_title(name, group)),
+      );
+
+      Tabs::new(titles)
+         .select(selected)
+         .highlight_style(Style::new().reversed())
+         .divider("│")
+         .render(area, buf);
+   }
+}
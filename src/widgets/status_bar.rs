@@ -1,23 +1,48 @@
-use std::vec;
-
 use ratatui::{
    buffer::Buffer,
    layout::Rect,
-   style::Stylize,
+   style::{Style, Stylize},
    text::{Line, Span},
    widgets::Widget,
 };
 
 pub struct StatusBar {
    left: String,
+   left_style: Style,
+   /// Keys of an unfinished key sequence, shown next to the version.
+   pending_keys: String,
+   /// The protocol version of the daemon and whether it is compatible with this build.
+   daemon_version: Option<(String, bool)>,
 }
 
 impl StatusBar {
    pub fn new(left: &str) -> Self {
       Self {
          left: left.to_string(),
+         left_style: Style::new(),
+         pending_keys: String::new(),
+         daemon_version: None,
       }
    }
+
+   pub fn error(left: &str) -> Self {
+      Self {
+         left: left.to_string(),
+         left_style: Style::new().red(),
+         pending_keys: String::new(),
+         daemon_version: None,
+      }
+   }
+
+   pub fn pending_keys(mut self, keys: &str) -> Self {
+      self.pending_keys = keys.to_string();
+      self
+   }
+
+   pub fn daemon_version(mut self, version: Option<&(String, bool)>) -> Self {
+      self.daemon_version = version.cloned();
+      self
+   }
 }
 
 impl Widget for StatusBar {
@@ -33,7 +58,23 @@ impl Widget for StatusBar {
       let pkg_name = env!("CARGO_PKG_NAME");
       let pkg_ver = env!("CARGO_PKG_VERSION");
 
-      let right_len = pkg_name.chars().count() as u16 + 2 + pkg_ver.chars().count() as u16;
+      let pending_len = match self.pending_keys.chars().count() as u16 {
+         0 => 0,
+         n => n + MIN_SPACE,
+      };
+      let daemon_version = self
+         .daemon_version
+         .as_ref()
+         .map(|(version, compatible)| match compatible {
+            true => format!("  daemon v{version}"),
+            false => format!("  daemon v{version} (incompatible)"),
+         })
+         .unwrap_or_default();
+      let right_len = pkg_name.chars().count() as u16
+         + 2
+         + pkg_ver.chars().count() as u16
+         + daemon_version.chars().count() as u16
+         + pending_len;
 
       let left_text = {
          let needed = left_len + right_len + MIN_SPACE;
@@ -53,12 +94,21 @@ impl Widget for StatusBar {
          }
       };
 
-      Line::from(left_text).left_aligned().render(area, buf);
-      Line::from(vec![
-         Span::from(pkg_name).bold(),
-         Span::from(format!(" v{pkg_ver}")),
-      ])
-      .right_aligned()
-      .render(area, buf);
+      Line::from(left_text)
+         .style(self.left_style)
+         .left_aligned()
+         .render(area, buf);
+      let mut right = Vec::new();
+      if !self.pending_keys.is_empty() {
+         right.push(Span::from(self.pending_keys).yellow());
+         right.push(Span::from(" ".repeat(MIN_SPACE as usize)));
+      }
+      right.push(Span::from(pkg_name).bold());
+      right.push(Span::from(format!(" v{pkg_ver}")));
+      match self.daemon_version {
+         Some((_, false)) => right.push(Span::from(daemon_version).red().bold()),
+         _ => right.push(Span::from(daemon_version)),
+      }
+      Line::from(right).right_aligned().render(area, buf);
    }
 }
@@ -1,17 +1,30 @@
+use std::{
+   borrow::Cow,
+   cmp::Ordering,
+   collections::{BTreeSet, HashMap},
+   ops::RangeInclusive,
+};
+
+use chrono::{DateTime, Local, TimeDelta};
 use pueue_lib::{Task, TaskResult, TaskStatus};
+use serde::{Deserialize, Serialize};
+
 use ratatui::{
    buffer::Buffer,
    layout::{Constraint, Layout, Rect},
    style::Style,
+   text::{Line, Span},
    widgets::{
       Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Table, TableState,
    },
 };
 
+use crate::config::{Config, Theme};
+
 //TODO: truncate long command and path strings with "..."
 
-#[derive(Clone)]
-enum HeaderCell {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HeaderCell {
    Id,
    Status,
    Priority,
@@ -22,9 +35,45 @@ enum HeaderCell {
    Path,
    Start,
    End,
+   Duration,
 }
 
 impl HeaderCell {
+   pub const ALL: [HeaderCell; 11] = [
+      HeaderCell::Id,
+      HeaderCell::Status,
+      HeaderCell::Priority,
+      HeaderCell::EnqueueAt,
+      HeaderCell::Dependencies,
+      HeaderCell::Label,
+      HeaderCell::Command,
+      HeaderCell::Path,
+      HeaderCell::Start,
+      HeaderCell::End,
+      HeaderCell::Duration,
+   ];
+
+   /// The name of the column in commands, e.g. `sort enqueue_at desc`.
+   pub const fn name(&self) -> &str {
+      match self {
+         HeaderCell::Id => "id",
+         HeaderCell::Status => "status",
+         HeaderCell::Priority => "priority",
+         HeaderCell::EnqueueAt => "enqueue_at",
+         HeaderCell::Dependencies => "dependencies",
+         HeaderCell::Label => "label",
+         HeaderCell::Command => "command",
+         HeaderCell::Path => "path",
+         HeaderCell::Start => "start",
+         HeaderCell::End => "end",
+         HeaderCell::Duration => "duration",
+      }
+   }
+
+   pub fn from_name(name: &str) -> Option<Self> {
+      Self::ALL.into_iter().find(|column| column.name() == name)
+   }
+
    pub const fn as_str(&self) -> &str {
       match self {
          HeaderCell::Id => "Id",
@@ -37,6 +86,7 @@ impl HeaderCell {
          HeaderCell::Path => "Path",
          HeaderCell::Start => "Start",
          HeaderCell::End => "End",
+         HeaderCell::Duration => "Duration",
       }
    }
 }
@@ -47,45 +97,197 @@ impl<'a> From<HeaderCell> for Cell<'a> {
    }
 }
 
-const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// A column to sort the tasks by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortKey {
+   pub column: HeaderCell,
+   pub descending: bool,
+}
+
+impl SortKey {
+   /// Compare two tasks by this column, with `now` as the end of running tasks.
+   pub fn compare(&self, a: &Task, b: &Task, now: DateTime<Local>) -> Ordering {
+      let ordering = match self.column {
+         HeaderCell::Id => a.id.cmp(&b.id),
+         HeaderCell::Status => status_rank(&a.status).cmp(&status_rank(&b.status)),
+         HeaderCell::Priority => a.priority.cmp(&b.priority),
+         HeaderCell::EnqueueAt => enqueue_at(a).cmp(&enqueue_at(b)),
+         HeaderCell::Dependencies => a.dependencies.cmp(&b.dependencies),
+         HeaderCell::Label => a.label.cmp(&b.label),
+         HeaderCell::Command => a.command.cmp(&b.command),
+         HeaderCell::Path => a.path.cmp(&b.path),
+         HeaderCell::Start => a.start_and_end().0.cmp(&b.start_and_end().0),
+         HeaderCell::End => a.start_and_end().1.cmp(&b.start_and_end().1),
+         HeaderCell::Duration => duration(a, now).cmp(&duration(b, now)),
+      };
+      if self.descending {
+         ordering.reverse()
+      } else {
+         ordering
+      }
+   }
+}
+
+/// Order the statuses from active to finished.
+fn status_rank(status: &TaskStatus) -> u8 {
+   match status {
+      TaskStatus::Running { .. } => 0,
+      TaskStatus::Paused { .. } => 1,
+      TaskStatus::Queued { .. } => 2,
+      TaskStatus::Stashed { .. } => 3,
+      TaskStatus::Locked { .. } => 4,
+      TaskStatus::Done {
+         result: TaskResult::Success,
+         ..
+      } => 5,
+      TaskStatus::Done { .. } => 6,
+   }
+}
+
+fn enqueue_at(task: &Task) -> Option<DateTime<Local>> {
+   match task.status {
+      TaskStatus::Stashed { enqueue_at } => enqueue_at,
+      _ => None,
+   }
+}
+
+/// How long a task has been running, or ran if it finished.
+pub fn duration(task: &Task, now: DateTime<Local>) -> Option<TimeDelta> {
+   match task.start_and_end() {
+      (Some(start), end) => Some(end.unwrap_or(now) - start),
+      _ => None,
+   }
+}
 
-pub struct TaskTable {
-   tasks: Vec<Task>,
+pub fn format_duration(duration: TimeDelta) -> String {
+   format!(
+      "{:02}:{:02}:{:02}",
+      duration.num_hours(),
+      duration.num_minutes() % 60,
+      duration.num_seconds() % 60
+   )
 }
 
-impl TaskTable {
-   pub fn new(tasks: &[Task]) -> Self {
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Char positions to highlight per column, e.g. the characters matched by a search.
+pub type CellHighlights = HashMap<HeaderCell, Vec<usize>>;
+
+/// Only the visible rows are built, and they are cached in the [`TaskTableState`] together with
+/// the column widths until the [generation](TaskTable::generation) changes.
+pub struct TaskTable<'a> {
+   tasks: &'a [Task],
+   highlights: Option<&'a HashMap<usize, CellHighlights>>,
+   columns: Cow<'a, [HeaderCell]>,
+   theme: Cow<'a, Theme>,
+   sort: &'a [SortKey],
+   generation: Option<u64>,
+}
+
+impl<'a> TaskTable<'a> {
+   pub fn new(tasks: &'a [Task]) -> Self {
       Self {
-         tasks: tasks.to_vec(),
+         tasks,
+         highlights: None,
+         columns: Cow::Owned(Config::default().columns),
+         theme: Cow::Owned(Theme::default()),
+         sort: &[],
+         generation: None,
       }
    }
 
-   fn task_to_row<'a>(task: &Task, header: &[HeaderCell]) -> Row<'a> {
+   /// Set the columns to show, in this order.
+   pub fn columns(mut self, columns: &'a [HeaderCell]) -> Self {
+      self.columns = Cow::Borrowed(columns);
+      self
+   }
+
+   pub fn theme(mut self, theme: &'a Theme) -> Self {
+      self.theme = Cow::Borrowed(theme);
+      self
+   }
+
+   /// Set the highlighted characters of each task, keyed by task id.
+   pub fn highlights(mut self, highlights: &'a HashMap<usize, CellHighlights>) -> Self {
+      self.highlights = Some(highlights);
+      self
+   }
+
+   /// Set the keys the tasks are sorted by, to show them in the header.
+   pub fn sort(mut self, sort: &'a [SortKey]) -> Self {
+      self.sort = sort;
+      self
+   }
+
+   /// Set a number which changes whenever the tasks, highlights, columns, sort or theme change.
+   ///
+   /// Without it, nothing is cached.
+   pub fn generation(mut self, generation: u64) -> Self {
+      self.generation = Some(generation);
+      self
+   }
+
+   fn task_to_row(
+      task: &Task,
+      header: &[HeaderCell],
+      highlights: Option<&CellHighlights>,
+      theme: &Theme,
+   ) -> Row<'static> {
       let cells: Vec<Cell> = header
          .iter()
-         .map(|header_cell| Self::task_to_cell(task, header_cell))
+         .map(
+            |header_cell| match highlights.and_then(|h| h.get(header_cell)) {
+               Some(positions) => Self::highlighted_cell(task, header_cell, positions, theme),
+               None => Self::task_to_cell(task, header_cell, theme),
+            },
+         )
          .collect();
 
       Row::new(cells)
    }
 
-   fn task_to_cell<'a>(task: &Task, header_cell: &HeaderCell) -> Cell<'a> {
+   fn highlighted_cell(
+      task: &Task,
+      header_cell: &HeaderCell,
+      positions: &[usize],
+      theme: &Theme,
+   ) -> Cell<'static> {
+      let content = match header_cell {
+         HeaderCell::Id => task.id.to_string(),
+         HeaderCell::Label => task.label.clone().unwrap_or_default(),
+         HeaderCell::Command => task.command.clone(),
+         HeaderCell::Path => task.path.to_string_lossy().to_string(),
+         _ => return Self::task_to_cell(task, header_cell, theme),
+      };
+      let spans: Vec<Span> = content
+         .chars()
+         .enumerate()
+         .map(|(i, c)| {
+            if positions.contains(&i) {
+               Span::styled(c.to_string(), theme.search_match)
+            } else {
+               Span::raw(c.to_string())
+            }
+         })
+         .collect();
+      Cell::new(Line::from(spans))
+   }
+
+   fn task_to_cell(task: &Task, header_cell: &HeaderCell, theme: &Theme) -> Cell<'static> {
       match header_cell {
          HeaderCell::Id => Cell::new(task.id.to_string()).style(Style::new()),
          HeaderCell::Status => {
             let content = Self::task_status_to_string(&task.status);
             let style = match &task.status {
-               TaskStatus::Locked { .. } => Style::new(),
-               TaskStatus::Stashed { .. } => Style::new().yellow(),
-               TaskStatus::Queued { .. } => Style::new().yellow(),
-               TaskStatus::Running { .. } => Style::new().green(),
-               TaskStatus::Paused { .. } => Style::new(),
+               TaskStatus::Locked { .. } | TaskStatus::Paused { .. } => Style::new().bold(),
+               TaskStatus::Stashed { .. } | TaskStatus::Queued { .. } => theme.status_queued,
+               TaskStatus::Running { .. } => theme.status_running,
                TaskStatus::Done { result, .. } => match result {
-                  TaskResult::Success => Style::new().green(),
-                  _ => Style::new().red(),
+                  TaskResult::Success => theme.status_success,
+                  _ => theme.status_failed,
                },
             };
-            Cell::new(content).style(style.bold())
+            Cell::new(content).style(style)
          }
          HeaderCell::Priority => Cell::new(task.priority.to_string()).style(Style::new()),
          HeaderCell::EnqueueAt => {
@@ -122,23 +324,45 @@ impl TaskTable {
             };
             Cell::new(content).style(Style::new())
          }
+         HeaderCell::Duration => {
+            let content = duration(task, Local::now()).map_or(String::new(), format_duration);
+            Cell::new(content).style(Style::new())
+         }
+      }
+   }
+
+   /// The name of a column with an arrow if the tasks are sorted by it, numbered if there are
+   /// several sort keys, e.g. `Status ▼1`.
+   fn header_label(column: HeaderCell, sort: &[SortKey]) -> String {
+      let Some(i) = sort.iter().position(|key| key.column == column) else {
+         return column.as_str().to_string();
+      };
+      let arrow = if sort[i].descending { '▼' } else { '▲' };
+      if sort.len() > 1 {
+         format!("{} {arrow}{}", column.as_str(), i + 1)
+      } else {
+         format!("{} {arrow}", column.as_str())
       }
    }
 
-   fn task_status_to_string(status: &TaskStatus) -> String {
+   pub fn task_status_to_string(status: &TaskStatus) -> String {
       match &status {
-         TaskStatus::Done { result, .. } => match result {
-            TaskResult::Success => "Success".to_string(),
-            TaskResult::Failed(code) => format!("Failed ({})", code),
-            TaskResult::FailedToSpawn(_) => "Failed to spawn".to_string(),
-            TaskResult::Killed => "Killed".to_string(),
-            TaskResult::Errored => "Errored".to_string(),
-            TaskResult::DependencyFailed => "Dependency failed".to_string(),
-         },
+         TaskStatus::Done { result, .. } => Self::task_result_to_string(result),
          _ => status.to_string(),
       }
    }
 
+   pub fn task_result_to_string(result: &TaskResult) -> String {
+      match result {
+         TaskResult::Success => "Success".to_string(),
+         TaskResult::Failed(code) => format!("Failed ({})", code),
+         TaskResult::FailedToSpawn(_) => "Failed to spawn".to_string(),
+         TaskResult::Killed => "Killed".to_string(),
+         TaskResult::Errored => "Errored".to_string(),
+         TaskResult::DependencyFailed => "Dependency failed".to_string(),
+      }
+   }
+
    fn dependencies_to_string(dependencies: &[usize]) -> String {
       dependencies
          .iter()
@@ -147,41 +371,43 @@ impl TaskTable {
          .join(", ")
    }
 
-   fn tasks_to_header(tasks: &[Task]) -> Vec<HeaderCell> {
-      let (has_prio, has_enqueue_at, has_deps, has_label) =
-         tasks.iter().fold((false, false, false, false), |acc, t| {
-            (
-               acc.0 || t.priority != 0,
-               acc.1
-                  || matches!(
-                     t.status,
-                     TaskStatus::Stashed {
-                        enqueue_at: Some(_)
-                     }
-                  ),
-               acc.2 || !t.dependencies.is_empty(),
-               acc.3 || t.label.is_some(),
-            )
-         });
+   /// The configured columns, without optional ones that no task has a value for.
+   fn tasks_to_header(tasks: &[Task], columns: &[HeaderCell]) -> Vec<HeaderCell> {
+      let (has_prio, has_enqueue_at, has_deps, has_label, has_duration) =
+         tasks
+            .iter()
+            .fold((false, false, false, false, false), |acc, t| {
+               (
+                  acc.0 || t.priority != 0,
+                  acc.1
+                     || matches!(
+                        t.status,
+                        TaskStatus::Stashed {
+                           enqueue_at: Some(_)
+                        }
+                     ),
+                  acc.2 || !t.dependencies.is_empty(),
+                  acc.3 || t.label.is_some(),
+                  acc.4 || t.start_and_end().0.is_some(),
+               )
+            });
+
+      columns
+         .iter()
+         .copied()
+         .filter(|column| match column {
+            HeaderCell::Priority => has_prio,
+            HeaderCell::EnqueueAt => has_enqueue_at,
+            HeaderCell::Dependencies => has_deps,
+            HeaderCell::Label => has_label,
+            HeaderCell::Duration => has_duration,
+            _ => true,
+         })
+         .collect()
+   }
 
-      [
-         Some(HeaderCell::Id),
-         Some(HeaderCell::Status),
-         has_prio.then(|| HeaderCell::Priority),
-         has_enqueue_at.then(|| HeaderCell::EnqueueAt),
-         has_deps.then(|| HeaderCell::Dependencies),
-         has_label.then(|| HeaderCell::Label),
-         Some(HeaderCell::Command),
-         Some(HeaderCell::Path),
-         Some(HeaderCell::Start),
-         Some(HeaderCell::End),
-      ]
-      .into_iter()
-      .flatten()
-      .collect()
-   }
-
-   fn calc_widths(header: &[HeaderCell], tasks: &[Task]) -> Vec<Constraint> {
+   fn calc_widths(header: &[HeaderCell], tasks: &[Task], sort: &[SortKey]) -> Vec<Constraint> {
+      let label_width = |column: HeaderCell| Self::header_label(column, sort).chars().count();
       let (
          max_id_width,
          max_status_width,
@@ -190,11 +416,11 @@ impl TaskTable {
          max_label_width,
       ) = tasks.iter().fold(
          (
-            HeaderCell::Id.as_str().len(),
-            HeaderCell::Status.as_str().len(),
-            HeaderCell::Priority.as_str().len(),
-            HeaderCell::Dependencies.as_str().len(),
-            HeaderCell::Label.as_str().len(),
+            label_width(HeaderCell::Id),
+            label_width(HeaderCell::Status),
+            label_width(HeaderCell::Priority),
+            label_width(HeaderCell::Dependencies),
+            label_width(HeaderCell::Label),
          ),
          |(id, status, prio, deps, label), t| {
             (
@@ -214,8 +440,9 @@ impl TaskTable {
             HeaderCell::Status => Constraint::Max(max_status_width as u16),
             HeaderCell::Priority => Constraint::Max(max_priority_width as u16),
             HeaderCell::EnqueueAt | HeaderCell::Start | HeaderCell::End => {
-               Constraint::Max("YYYY-MM-DD HH:MM:SS".len() as u16)
+               Constraint::Max("YYYY-MM-DD HH:MM:SS".len().max(label_width(*col)) as u16)
             }
+            HeaderCell::Duration => Constraint::Max("HH:MM:SS".len().max(label_width(*col)) as u16),
             HeaderCell::Dependencies => Constraint::Max(max_dependencies_width as u16),
             HeaderCell::Label => Constraint::Max(max_label_width as u16),
             HeaderCell::Command | HeaderCell::Path => {
@@ -226,25 +453,169 @@ impl TaskTable {
    }
 }
 
-pub type TaskTableState = (TableState, ScrollbarState);
+/// The first visible row, scrolled just enough to show the selected row.
+fn scroll_offset(offset: usize, selected: Option<usize>, height: usize, len: usize) -> usize {
+   let offset = match selected {
+      Some(i) if i < offset => i,
+      Some(i) if height > 0 && i >= offset + height => i + 1 - height,
+      _ => offset,
+   };
+   offset.min(len.saturating_sub(height))
+}
 
-impl StatefulWidget for TaskTable {
-   type State = TaskTableState;
+/// What a [`TaskTable`] computed for one generation of its tasks.
+#[derive(Debug, Default, Clone)]
+struct TableCache {
+   generation: u64,
+   header: Vec<HeaderCell>,
+   widths: Vec<Constraint>,
+   /// The rows built so far by task id, with the highlights they have been built with.
+   rows: HashMap<usize, (Option<CellHighlights>, Row<'static>)>,
+}
 
-   fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-      (*state).1 = state.1.content_length(self.tasks.len());
+/// Cursor, scroll position and marked rows of a [`TaskTable`].
+#[derive(Debug, Default, Clone)]
+pub struct TaskTableState {
+   table: TableState,
+   /// Id of the selected task, which the selection follows when the tasks change.
+   selected_id: Option<usize>,
+   /// The index of the first visible row.
+   offset: usize,
+   scrollbar: ScrollbarState,
+   cache: Option<TableCache>,
+   /// Ids of the tasks marked for a bulk operation.
+   marked: BTreeSet<usize>,
+   /// Id of the task a visual range has been started at.
+   visual_anchor: Option<usize>,
+}
+
+impl TaskTableState {
+   pub fn new() -> Self {
+      Self {
+         table: TableState::new().with_selected(0),
+         ..Self::default()
+      }
+   }
+
+   pub fn selected(&self) -> Option<usize> {
+      self.table.selected()
+   }
+
+   pub fn select(&mut self, i: usize) {
+      self.table.select(Some(i));
+      self.scrollbar = self.scrollbar.position(i);
+   }
+
+   pub fn selected_id(&self) -> Option<usize> {
+      self.selected_id
+   }
+
+   /// Select the task in row `i`, or the last one if there are fewer rows.
+   pub fn select_task(&mut self, tasks: &[Task], i: usize) {
+      match tasks.len().checked_sub(1) {
+         Some(last) => {
+            let i = i.min(last);
+            self.select(i);
+            self.selected_id = Some(tasks[i].id);
+         }
+         None => {
+            self.table.select(None);
+            self.selected_id = None;
+         }
+      }
+   }
+
+   /// Find the selected task again after `tasks` changed.
+   ///
+   /// If it is gone, the task which took its row is selected, or the new last one.
+   pub fn follow_selection(&mut self, tasks: &[Task]) {
+      let row = self
+         .selected_id
+         .and_then(|id| tasks.iter().position(|task| task.id == id))
+         .or(self.selected())
+         .unwrap_or_default();
+      self.select_task(tasks, row);
+   }
+
+   pub fn is_visual(&self) -> bool {
+      self.visual_anchor.is_some()
+   }
+
+   pub fn toggle_mark(&mut self, task_id: usize) {
+      if !self.marked.remove(&task_id) {
+         self.marked.insert(task_id);
+      }
+   }
+
+   /// Start a visual range at the selected row, or mark the rows of the current one.
+   pub fn toggle_visual(&mut self, tasks: &[Task]) {
+      match self.visual_anchor {
+         Some(_) => {
+            let ids: Vec<usize> = self.marked_ids(tasks);
+            self.marked.extend(ids);
+            self.visual_anchor = None;
+         }
+         None => {
+            self.visual_anchor = self.selected().and_then(|i| tasks.get(i)).map(|t| t.id);
+         }
+      }
+   }
+
+   /// Mark all `tasks`, or unmark them if they are all marked already.
+   pub fn toggle_all(&mut self, tasks: &[Task]) {
+      self.visual_anchor = None;
+      if tasks.iter().all(|task| self.marked.contains(&task.id)) {
+         self.marked.clear();
+      } else {
+         self.marked.extend(tasks.iter().map(|task| task.id));
+      }
+   }
+
+   pub fn clear_marks(&mut self) {
+      self.marked.clear();
+      self.visual_anchor = None;
+   }
+
+   /// Forget marks of tasks which are not shown anymore.
+   pub fn retain_marks(&mut self, tasks: &[Task]) {
+      let visible: BTreeSet<usize> = tasks.iter().map(|task| task.id).collect();
+      self.marked.retain(|id| visible.contains(id));
+      self.visual_anchor = self.visual_anchor.filter(|id| visible.contains(id));
+   }
 
-      let header = Self::tasks_to_header(&self.tasks);
+   /// Ids of the marked tasks including the visual range, in the order of `tasks`.
+   pub fn marked_ids(&self, tasks: &[Task]) -> Vec<usize> {
+      let range = self.visual_range(tasks);
+      tasks
+         .iter()
+         .enumerate()
+         .filter(|(i, task)| {
+            self.marked.contains(&task.id) || range.as_ref().is_some_and(|r| r.contains(i))
+         })
+         .map(|(_, task)| task.id)
+         .collect()
+   }
 
-      let widths = Self::calc_widths(&header, &self.tasks);
-      let rows: Vec<Row> = self
-         .tasks
+   fn visual_range(&self, tasks: &[Task]) -> Option<RangeInclusive<usize>> {
+      let anchor = tasks
          .iter()
-         .map(|task| Self::task_to_row(task, &header))
-         .collect();
+         .position(|task| Some(task.id) == self.visual_anchor)?;
+      let selected = self.selected()?.min(tasks.len().saturating_sub(1));
+      Some(anchor.min(selected)..=anchor.max(selected))
+   }
+}
+
+impl StatefulWidget for TaskTable<'_> {
+   type State = TaskTableState;
 
-      let is_needed_scrollbar = area.height.saturating_sub(1) < self.tasks.len() as u16;
-      let (table_area, scroll_bar_area) = (|| {
+   fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+      let len = self.tasks.len();
+      state.scrollbar = state.scrollbar.content_length(len);
+      let marked: BTreeSet<usize> = state.marked.clone();
+      let visual_range = state.visual_range(self.tasks);
+
+      let is_needed_scrollbar = (area.height.saturating_sub(1) as usize) < len;
+      let (table_area, scroll_bar_area) = {
          if is_needed_scrollbar {
             let [table_area, scroll_bar_area] =
                Layout::horizontal([Constraint::Fill(1), Constraint::Length(2)]).areas(area);
@@ -259,25 +630,262 @@ impl StatefulWidget for TaskTable {
          } else {
             (area, None)
          }
-      })();
+      };
+
+      // One line is taken by the header.
+      let height = table_area.height.saturating_sub(1) as usize;
+      let selected = state.selected().map(|i| i.min(len.saturating_sub(1)));
+      state.offset = scroll_offset(state.offset, selected, height, len);
+      let visible = state.offset..(state.offset + height).min(len);
+
+      if self.generation.is_none()
+         || state.cache.as_ref().map(|cache| cache.generation) != self.generation
+      {
+         let header = Self::tasks_to_header(self.tasks, &self.columns);
+         state.cache = Some(TableCache {
+            generation: self.generation.unwrap_or_default(),
+            widths: Self::calc_widths(&header, self.tasks, self.sort),
+            header,
+            rows: HashMap::new(),
+         });
+      }
+      let cache = state.cache.get_or_insert_default();
+      // The duration of running tasks grows, so their rows can't be kept.
+      let has_duration = cache.header.contains(&HeaderCell::Duration);
+
+      let rows: Vec<Row> = visible
+         .clone()
+         .map(|i| {
+            let task = &self.tasks[i];
+            let highlights = self.highlights.and_then(|h| h.get(&task.id));
+            let build = || Self::task_to_row(task, &cache.header, highlights, &self.theme);
+            let row = if has_duration && task.is_running() {
+               build()
+            } else {
+               match cache.rows.get(&task.id) {
+                  Some((built_with, row)) if built_with.as_ref() == highlights => row.clone(),
+                  _ => {
+                     let row = build();
+                     cache
+                        .rows
+                        .insert(task.id, (highlights.cloned(), row.clone()));
+                     row
+                  }
+               }
+            };
+            if marked.contains(&task.id) || visual_range.as_ref().is_some_and(|r| r.contains(&i)) {
+               row.style(self.theme.marked_row)
+            } else {
+               row
+            }
+         })
+         .collect();
 
-      let table = Table::new(rows, widths)
+      let table = Table::new(rows, cache.widths.clone())
          .header(Row::new(
-            header
+            cache
+               .header
                .iter()
-               .cloned()
-               .map(|hc| Cell::from(hc))
+               .map(|&cell| Cell::new(Self::header_label(cell, self.sort)).style(self.theme.header))
                .collect::<Vec<Cell>>(),
          ))
          .column_spacing(2)
-         .row_highlight_style(Style::new().on_black());
-      table.render(table_area, buf, &mut state.0);
+         .row_highlight_style(self.theme.selected_row);
+      let mut table_state =
+         TableState::new().with_selected(selected.map(|i| i.saturating_sub(visible.start)));
+      table.render(table_area, buf, &mut table_state);
 
       if let Some(scroll_bar_area) = scroll_bar_area {
          let scroll_bar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
             .end_symbol(None);
-         scroll_bar.render(scroll_bar_area, buf, &mut state.1);
+         scroll_bar.render(scroll_bar_area, buf, &mut state.scrollbar);
       }
    }
 }
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   use std::{collections::HashMap, path::PathBuf};
+
+   use pretty_assertions::assert_eq;
+
+   fn tasks(ids: &[usize]) -> Vec<Task> {
+      ids.iter()
+         .map(|&id| {
+            let mut task = Task::new(
+               "sleep 1".to_string(),
+               PathBuf::from("/"),
+               HashMap::new(),
+               "default".to_string(),
+               TaskStatus::Stashed { enqueue_at: None },
+               vec![],
+               0,
+               None,
+            );
+            task.id = id;
+            task
+         })
+         .collect()
+   }
+
+   #[test]
+   fn test_render_visible_rows() {
+      let ids: Vec<usize> = (0..1000).collect();
+      let tasks = tasks(&ids);
+      let mut state = TaskTableState::new();
+      let area = Rect::new(0, 0, 120, 6);
+      let mut buf = Buffer::empty(area);
+
+      state.select(500);
+      TaskTable::new(&tasks)
+         .generation(1)
+         .render(area, &mut buf, &mut state);
+      let cache = state.cache.as_ref().unwrap();
+      assert_eq!(cache.rows.len(), 5);
+      assert_eq!(state.offset, 496);
+      let line = |buf: &Buffer, y: u16| {
+         (0..area.width)
+            .map(|x| buf[(x, y)].symbol())
+            .collect::<String>()
+      };
+      assert!(line(&buf, 1).starts_with("496 "), "{:?}", line(&buf, 1));
+      assert!(line(&buf, 5).starts_with("500 "), "{:?}", line(&buf, 5));
+
+      // Scrolling up keeps the selected row at the top.
+      state.select(490);
+      TaskTable::new(&tasks)
+         .generation(1)
+         .render(area, &mut buf, &mut state);
+      assert_eq!(state.offset, 490);
+      assert_eq!(state.cache.as_ref().unwrap().rows.len(), 10);
+
+      // Rows follow their task when the order changes within a generation.
+      let mut reordered = tasks.clone();
+      reordered.swap(490, 491);
+      TaskTable::new(&reordered)
+         .generation(1)
+         .render(area, &mut buf, &mut state);
+      assert!(line(&buf, 1).starts_with("491 "), "{:?}", line(&buf, 1));
+      assert!(line(&buf, 2).starts_with("490 "), "{:?}", line(&buf, 2));
+
+      // Rows are built again when their highlights change.
+      let highlights = HashMap::from([(490, HashMap::from([(HeaderCell::Command, vec![0])]))]);
+      TaskTable::new(&reordered)
+         .generation(1)
+         .highlights(&highlights)
+         .render(area, &mut buf, &mut state);
+      assert_eq!(
+         state.cache.as_ref().unwrap().rows[&490].0,
+         highlights.get(&490).cloned()
+      );
+
+      // A new generation starts over.
+      TaskTable::new(&tasks[..3])
+         .generation(2)
+         .render(area, &mut buf, &mut state);
+      assert_eq!(state.offset, 0);
+      assert_eq!(state.cache.as_ref().unwrap().rows.len(), 3);
+   }
+
+   #[test]
+   fn test_sort_by_duration() {
+      let now = Local::now();
+      let mut tasks = tasks(&[0, 1, 2, 3]);
+      let done = |start: i64, end: i64| TaskStatus::Done {
+         enqueued_at: now,
+         start: now - TimeDelta::minutes(start),
+         end: now - TimeDelta::minutes(end),
+         result: TaskResult::Success,
+      };
+      tasks[0].status = done(10, 5);
+      tasks[1].status = TaskStatus::Running {
+         enqueued_at: now,
+         start: now - TimeDelta::minutes(30),
+      };
+      tasks[3].status = done(3, 2);
+
+      let key = SortKey {
+         column: HeaderCell::Duration,
+         descending: true,
+      };
+      tasks.sort_by(|a, b| key.compare(a, b, now).then(a.id.cmp(&b.id)));
+      assert_eq!(
+         tasks.iter().map(|task| task.id).collect::<Vec<_>>(),
+         [1, 0, 3, 2]
+      );
+      let running = tasks.iter().find(|task| task.id == 1).unwrap();
+      assert_eq!(
+         duration(running, now).map(format_duration).as_deref(),
+         Some("00:30:00")
+      );
+
+      let sort = [
+         key,
+         SortKey {
+            column: HeaderCell::Id,
+            descending: false,
+         },
+      ];
+      assert_eq!(
+         TaskTable::header_label(HeaderCell::Duration, &sort),
+         "Duration ▼1"
+      );
+      assert_eq!(TaskTable::header_label(HeaderCell::Id, &sort), "Id ▲2");
+      assert_eq!(TaskTable::header_label(HeaderCell::Id, &sort[1..]), "Id ▲");
+      assert_eq!(TaskTable::header_label(HeaderCell::Status, &sort), "Status");
+   }
+
+   #[test]
+   fn test_follow_selection() {
+      let mut state = TaskTableState::new();
+      state.follow_selection(&[]);
+      assert_eq!((state.selected(), state.selected_id()), (None, None));
+
+      state.follow_selection(&tasks(&[1, 2, 3, 4]));
+      assert_eq!((state.selected(), state.selected_id()), (Some(0), Some(1)));
+      state.select_task(&tasks(&[1, 2, 3, 4]), 2);
+
+      // A new task above moves the selection down with its task.
+      state.follow_selection(&tasks(&[0, 1, 2, 3, 4]));
+      assert_eq!((state.selected(), state.selected_id()), (Some(3), Some(3)));
+
+      // The next task takes the place of a removed one.
+      state.follow_selection(&tasks(&[0, 1, 2, 4]));
+      assert_eq!((state.selected(), state.selected_id()), (Some(3), Some(4)));
+
+      // Without a next task, the previous one is selected.
+      state.follow_selection(&tasks(&[0, 1]));
+      assert_eq!((state.selected(), state.selected_id()), (Some(1), Some(1)));
+
+      state.follow_selection(&[]);
+      assert_eq!((state.selected(), state.selected_id()), (None, None));
+   }
+
+   #[test]
+   fn test_marked_ids_with_visual_range() {
+      let tasks = tasks(&[3, 5, 7, 9, 11]);
+      let mut state = TaskTableState::new();
+      state.toggle_mark(11);
+
+      state.select(3);
+      state.toggle_visual(&tasks);
+      state.select(1);
+      assert_eq!(state.marked_ids(&tasks), vec![5, 7, 9, 11]);
+
+      // Ending the visual range keeps its rows marked.
+      state.toggle_visual(&tasks);
+      state.select(0);
+      assert_eq!(state.marked_ids(&tasks), vec![5, 7, 9, 11]);
+
+      state.retain_marks(&tasks[..3]);
+      assert_eq!(state.marked_ids(&tasks), vec![5, 7]);
+
+      state.toggle_all(&tasks);
+      assert_eq!(state.marked_ids(&tasks), vec![3, 5, 7, 9, 11]);
+      state.toggle_all(&tasks);
+      assert_eq!(state.marked_ids(&tasks), Vec::<usize>::new());
+   }
+}
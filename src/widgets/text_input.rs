@@ -0,0 +1,170 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+   buffer::Buffer,
+   layout::{Position, Rect},
+   style::{Style, Stylize},
+   text::{Line, Span},
+   widgets::{StatefulWidget, Widget},
+};
+
+/// The editable content of a [`TextInput`].
+#[derive(Default, Debug, Clone)]
+pub struct TextInputState {
+   value: String,
+   /// Cursor position in chars.
+   cursor: usize,
+}
+
+impl TextInputState {
+   pub fn new(value: &str) -> Self {
+      Self {
+         value: value.to_string(),
+         cursor: value.chars().count(),
+      }
+   }
+
+   pub fn value(&self) -> &str {
+      &self.value
+   }
+
+   pub fn clear(&mut self) {
+      self.value.clear();
+      self.cursor = 0;
+   }
+
+   /// Apply a key press to the input.
+   ///
+   /// Returns whether the value changed.
+   pub fn handle_key_event(&mut self, key: KeyEvent) -> bool {
+      let KeyEvent {
+         code, modifiers, ..
+      } = key;
+      let len = self.value.chars().count();
+      match (code, modifiers) {
+         (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+            self.remove_range(0, self.cursor);
+            self.cursor = 0;
+            true
+         }
+         (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+            let chars: Vec<char> = self.value.chars().collect();
+            let mut start = self.cursor;
+            while start > 0 && chars[start - 1] == ' ' {
+               start -= 1;
+            }
+            while start > 0 && chars[start - 1] != ' ' {
+               start -= 1;
+            }
+            let changed = start != self.cursor;
+            self.remove_range(start, self.cursor);
+            self.cursor = start;
+            changed
+         }
+         (KeyCode::Char('a'), KeyModifiers::CONTROL) | (KeyCode::Home, _) => {
+            self.cursor = 0;
+            false
+         }
+         (KeyCode::Char('e'), KeyModifiers::CONTROL) | (KeyCode::End, _) => {
+            self.cursor = len;
+            false
+         }
+         (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+            let index = self.byte_index(self.cursor);
+            self.value.insert(index, c);
+            self.cursor += 1;
+            true
+         }
+         (KeyCode::Backspace, _) if self.cursor > 0 => {
+            self.remove_range(self.cursor - 1, self.cursor);
+            self.cursor -= 1;
+            true
+         }
+         (KeyCode::Delete, _) if self.cursor < len => {
+            self.remove_range(self.cursor, self.cursor + 1);
+            true
+         }
+         (KeyCode::Left, _) => {
+            self.cursor = self.cursor.saturating_sub(1);
+            false
+         }
+         (KeyCode::Right, _) => {
+            self.cursor = (self.cursor + 1).min(len);
+            false
+         }
+         _ => false,
+      }
+   }
+
+   fn byte_index(&self, char_index: usize) -> usize {
+      self
+         .value
+         .char_indices()
+         .nth(char_index)
+         .map_or(self.value.len(), |(i, _)| i)
+   }
+
+   fn remove_range(&mut self, start: usize, end: usize) {
+      let (start, end) = (self.byte_index(start), self.byte_index(end));
+      self.value.replace_range(start..end, "");
+   }
+}
+
+/// A single line text input with a prompt in front of it.
+pub struct TextInput<'a> {
+   prompt: &'a str,
+   placeholder: &'a str,
+   focused: bool,
+}
+
+impl<'a> TextInput<'a> {
+   pub fn new(prompt: &'a str) -> Self {
+      Self {
+         prompt,
+         placeholder: "",
+         focused: true,
+      }
+   }
+
+   /// Text shown dimmed while the input is empty.
+   pub fn placeholder(mut self, placeholder: &'a str) -> Self {
+      self.placeholder = placeholder;
+      self
+   }
+
+   pub fn focused(mut self, focused: bool) -> Self {
+      self.focused = focused;
+      self
+   }
+
+   /// Where the terminal cursor should be placed when the input is rendered in `area`.
+   pub fn cursor_position(&self, area: Rect, state: &TextInputState) -> Position {
+      let offset = (self.prompt.chars().count() + state.cursor) as u16;
+      Position::new(
+         area
+            .x
+            .saturating_add(offset)
+            .min(area.right().saturating_sub(1)),
+         area.y,
+      )
+   }
+}
+
+impl StatefulWidget for TextInput<'_> {
+   type State = TextInputState;
+
+   fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+      let style = if self.focused {
+         Style::new()
+      } else {
+         Style::new().dim()
+      };
+      let value = if state.value.is_empty() {
+         Span::from(self.placeholder).dark_gray()
+      } else {
+         Span::from(state.value.as_str())
+      };
+      Line::from(vec![Span::from(self.prompt).bold(), value])
+         .style(style)
+         .render(area, buf);
+   }
+}